@@ -0,0 +1,26 @@
+#!/usr/bin/env bash
+# Regenerate the weights.rs of the pallets benchmarked by the node-template runtime. Run from the
+# repository root, on the reference hardware the weights are meant for.
+set -euo pipefail
+
+PALLETS=(
+	"pallet_poe:node-template/pallets/poe/src/weights.rs"
+	"pallet_validator_set:node-template/pallets/validator-set/src/weights.rs"
+)
+
+cargo build --release -p node-template --features runtime-benchmarks
+
+for entry in "${PALLETS[@]}"; do
+	pallet="${entry%%:*}"
+	output="${entry#*:}"
+	./target/release/node-template benchmark pallet \
+		--chain dev \
+		--execution wasm \
+		--wasm-execution compiled \
+		--pallet "$pallet" \
+		--extrinsic '*' \
+		--steps 50 \
+		--repeat 20 \
+		--output "$output" \
+		--template .maintain/frame-weight-template.hbs
+done
@@ -0,0 +1,66 @@
+//! Typed calls of the kitties and PoE pallets, ready for [`crate::Client::submit`].
+
+use node_template_runtime::{AccountId, Balance, Call, Runtime};
+
+/// Calls of `SubstrateKitties`. Kitties are designated by their DNA.
+pub mod kitties {
+	use super::*;
+
+	pub fn create_kitty() -> Call {
+		pallet_kitties::Call::<Runtime>::create_kitty {}.into()
+	}
+
+	pub fn breed_kitty(parent_1: [u8; 16], parent_2: [u8; 16]) -> Call {
+		pallet_kitties::Call::<Runtime>::breed_kitty { parent_1, parent_2 }.into()
+	}
+
+	pub fn transfer(to: AccountId, kitty: [u8; 16]) -> Call {
+		pallet_kitties::Call::<Runtime>::transfer { to, kitty_id: kitty }.into()
+	}
+
+	/// Buy a kitty for sale, paying at most `limit_price`.
+	pub fn buy_kitty(kitty: [u8; 16], limit_price: Balance) -> Call {
+		pallet_kitties::Call::<Runtime>::buy_kitty { kitty_id: kitty, limit_price }.into()
+	}
+
+	/// Put a kitty on sale, or off sale without a price.
+	pub fn set_price(kitty: [u8; 16], price: Option<Balance>) -> Call {
+		pallet_kitties::Call::<Runtime>::set_price { kitty_id: kitty, new_price: price }.into()
+	}
+}
+
+/// Calls of `PoeModule`.
+pub mod poe {
+	use super::*;
+	use pallet_poe::MerkleRoot;
+
+	pub fn create_claim(claim: Vec<u8>) -> Call {
+		pallet_poe::Call::<Runtime>::create_claim { claim }.into()
+	}
+
+	pub fn revoke_claim(claim: Vec<u8>) -> Call {
+		pallet_poe::Call::<Runtime>::revoke_claim { claim }.into()
+	}
+
+	/// Offer a claim to `to`, who takes it over with [`accept_claim_transfer`].
+	pub fn offer_claim_transfer(to: AccountId, claim: Vec<u8>) -> Call {
+		pallet_poe::Call::<Runtime>::offer_claim_transfer { to, claim }.into()
+	}
+
+	pub fn accept_claim_transfer(claim: Vec<u8>) -> Call {
+		pallet_poe::Call::<Runtime>::accept_claim_transfer { claim }.into()
+	}
+
+	pub fn cancel_claim_transfer(claim: Vec<u8>) -> Call {
+		pallet_poe::Call::<Runtime>::cancel_claim_transfer { claim }.into()
+	}
+
+	/// Notarize every leaf of a Merkle tree at once.
+	pub fn create_batch_claim(root: MerkleRoot) -> Call {
+		pallet_poe::Call::<Runtime>::create_batch_claim { root }.into()
+	}
+
+	pub fn dispute_claim(claim: Vec<u8>, reason: Vec<u8>) -> Call {
+		pallet_poe::Call::<Runtime>::dispute_claim { claim, reason }.into()
+	}
+}
@@ -0,0 +1,151 @@
+//! Signed extrinsics of the node-template runtime, built offline.
+
+use codec::Encode;
+use node_template_runtime::{
+	BlockHashCount, BlockNumber, Call, Hash, Index, Runtime, Signature, SignedExtra, SignedPayload,
+	UncheckedExtrinsic,
+};
+use sp_core::{sr25519, Pair};
+use sp_runtime::{generic::Era, AccountId32};
+
+/// How long a transaction stays valid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mortality {
+	/// Valid until its nonce is used, on any fork.
+	Immortal,
+	/// Valid for about `period` blocks after its checkpoint, rounded to a power of two.
+	Mortal { period: u64 },
+}
+
+impl Default for Mortality {
+	/// The longest period the runtime keeps the block hashes of.
+	fn default() -> Self {
+		let period = BlockHashCount::get().checked_next_power_of_two().map(|c| c / 2).unwrap_or(2);
+		Mortality::Mortal { period: period as u64 }
+	}
+}
+
+impl Mortality {
+	/// The era of a transaction whose lifetime starts at block `number`.
+	pub fn era(&self, number: BlockNumber) -> Era {
+		match self {
+			Mortality::Immortal => Era::Immortal,
+			Mortality::Mortal { period } => Era::mortal(*period, number as u64),
+		}
+	}
+}
+
+/// What a signature commits to about the chain, besides the lifetime of the transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChainContext {
+	pub genesis_hash: Hash,
+	pub spec_version: u32,
+	pub transaction_version: u32,
+}
+
+/// The block the lifetime of a mortal transaction starts at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+	pub number: BlockNumber,
+	pub hash: Hash,
+}
+
+/// The signed extensions of a transaction, as the runtime checks them.
+pub fn signed_extra(nonce: Index, era: Era) -> SignedExtra {
+	(
+		frame_system::CheckNonZeroSender::<Runtime>::new(),
+		frame_system::CheckSpecVersion::<Runtime>::new(),
+		frame_system::CheckTxVersion::<Runtime>::new(),
+		frame_system::CheckGenesis::<Runtime>::new(),
+		frame_system::CheckEra::<Runtime>::from(era),
+		frame_system::CheckNonce::<Runtime>::from(nonce),
+		frame_system::CheckWeight::<Runtime>::new(),
+		pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+	)
+}
+
+/// Sign `call` with the `nonce` of `signer`. A mortal transaction is valid from `checkpoint`, which
+/// must be a block of the chain the transaction is submitted to.
+pub fn sign(
+	signer: &sr25519::Pair,
+	call: Call,
+	nonce: Index,
+	mortality: Mortality,
+	chain: &ChainContext,
+	checkpoint: &Checkpoint,
+) -> UncheckedExtrinsic {
+	let era = mortality.era(checkpoint.number);
+	let era_hash = match mortality {
+		Mortality::Immortal => chain.genesis_hash,
+		Mortality::Mortal { .. } => checkpoint.hash,
+	};
+	let extra = signed_extra(nonce, era);
+
+	let raw_payload = SignedPayload::from_raw(
+		call.clone(),
+		extra.clone(),
+		(
+			(),
+			chain.spec_version,
+			chain.transaction_version,
+			chain.genesis_hash,
+			era_hash,
+			(),
+			(),
+			(),
+		),
+	);
+	let signature = raw_payload.using_encoded(|e| signer.sign(e));
+
+	UncheckedExtrinsic::new_signed(
+		call,
+		AccountId32::from(signer.public()).into(),
+		Signature::Sr25519(signature),
+		extra,
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_keyring::Sr25519Keyring::Alice;
+	use sp_runtime::traits::Verify;
+
+	#[test]
+	fn signature_covers_the_chain_and_checkpoint() {
+		let chain = ChainContext {
+			genesis_hash: Hash::repeat_byte(1),
+			spec_version: 100,
+			transaction_version: 1,
+		};
+		let checkpoint = Checkpoint { number: 42, hash: Hash::repeat_byte(2) };
+		let call: Call = pallet_kitties::Call::<Runtime>::create_kitty {}.into();
+
+		let xt = sign(&Alice.pair(), call.clone(), 7, Mortality::default(), &chain, &checkpoint);
+		let (_, signature, extra) = xt.signature.clone().unwrap();
+		assert_eq!(extra.5, frame_system::CheckNonce::<Runtime>::from(7));
+
+		let additional = (
+			(),
+			chain.spec_version,
+			chain.transaction_version,
+			chain.genesis_hash,
+			checkpoint.hash,
+			(),
+			(),
+			(),
+		);
+		let payload = SignedPayload::from_raw(call, extra, additional);
+		assert!(payload.using_encoded(|e| signature.verify(e, &Alice.to_account_id())));
+	}
+
+	#[test]
+	fn default_mortality_is_a_power_of_two() {
+		match Mortality::default() {
+			Mortality::Mortal { period } =>
+				assert!(period.is_power_of_two() && period <= BlockHashCount::get() as u64),
+			Mortality::Immortal => panic!("Transactions are mortal by default"),
+		}
+		assert_eq!(Mortality::Immortal.era(42), Era::Immortal);
+	}
+}
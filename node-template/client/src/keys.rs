@@ -0,0 +1,81 @@
+//! Storage keys of the runtime items read by the client and by the node's offline and RPC tools,
+//! hashed the way FRAME stores them.
+
+use codec::Encode;
+use sp_core::{blake2_128, twox_128, twox_64};
+
+/// The key of a storage value, the prefix of the entries of a map.
+pub fn item(pallet: &str, item: &str) -> Vec<u8> {
+	[twox_128(pallet.as_bytes()), twox_128(item.as_bytes())].concat()
+}
+
+/// The key of the entry `key` of a `Twox64Concat` map under `prefix`.
+pub fn twox_64_concat(prefix: Vec<u8>, key: &impl Encode) -> Vec<u8> {
+	let key = key.encode();
+	[prefix, twox_64(&key).to_vec(), key].concat()
+}
+
+/// The key of the entry `key` of a `Blake2_128Concat` map under `prefix`.
+pub fn blake2_128_concat(prefix: Vec<u8>, key: &impl Encode) -> Vec<u8> {
+	let key = key.encode();
+	[prefix, blake2_128(&key).to_vec(), key].concat()
+}
+
+/// `System::Events`, the events of the block.
+pub fn system_events() -> Vec<u8> {
+	item("System", "Events")
+}
+
+/// `Timestamp::Now`, the timestamp of the block in milliseconds.
+pub fn timestamp_now() -> Vec<u8> {
+	item("Timestamp", "Now")
+}
+
+/// `SubstrateKitties::CountForKitties`.
+pub fn kitty_count() -> Vec<u8> {
+	item("SubstrateKitties", "CountForKitties")
+}
+
+/// `SubstrateKitties::MarketplaceFee`.
+pub fn marketplace_fee() -> Vec<u8> {
+	item("SubstrateKitties", "MarketplaceFee")
+}
+
+/// The prefix of `SubstrateKitties::Kitties`.
+pub fn kitties() -> Vec<u8> {
+	item("SubstrateKitties", "Kitties")
+}
+
+/// The kitty with DNA `dna` in `SubstrateKitties::Kitties`.
+pub fn kitty(dna: &[u8; 16]) -> Vec<u8> {
+	twox_64_concat(kitties(), dna)
+}
+
+/// The DNA of the kitties of `owner` in `SubstrateKitties::KittiesOwned`.
+pub fn kitties_owned(owner: &impl Encode) -> Vec<u8> {
+	twox_64_concat(item("SubstrateKitties", "KittiesOwned"), owner)
+}
+
+/// The prefix of `PoeModule::Proofs`.
+pub fn proofs() -> Vec<u8> {
+	item("PoeModule", "Proofs")
+}
+
+/// The owner and creation block of `claim` in `PoeModule::Proofs`.
+pub fn proof(claim: &[u8]) -> Vec<u8> {
+	blake2_128_concat(proofs(), &claim.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Decode;
+
+	#[test]
+	fn map_keys_end_with_the_key() {
+		let claim = b"deed".to_vec();
+		let key = proof(&claim);
+		assert_eq!(Vec::<u8>::decode(&mut &key[32 + 16..]).unwrap(), claim);
+		assert_eq!(kitty(&[7u8; 16]).len(), 56);
+	}
+}
@@ -0,0 +1,315 @@
+//! A client of node-template nodes for back-end services: it builds, signs and submits kitty and
+//! PoE transactions over a node's WebSocket RPC.
+//!
+//! ```ignore
+//! let client = Client::connect("ws://127.0.0.1:9944").await?;
+//! let hash = client.submit(&Sr25519Keyring::Alice.pair(), calls::kitties::create_kitty()).await?;
+//! ```
+//!
+//! The client keeps the next nonce of every account it signed for, so transactions of an account
+//! can be submitted back to back without waiting for their inclusion. A failed submission forgets
+//! the nonce of its signer, to read it from the node again on the next transaction, and reads the
+//! runtime version again: after a runtime upgrade, transactions signed for the previous version are
+//! rejected until then.
+
+pub mod calls;
+mod extrinsic;
+pub mod keys;
+
+pub use extrinsic::{sign, signed_extra, ChainContext, Checkpoint, Mortality};
+pub use sc_transaction_pool_api::TransactionStatus;
+
+use codec::{Decode, Encode};
+use jsonrpsee::{
+	core::client::{ClientT, Subscription, SubscriptionClientT},
+	rpc_params,
+	ws_client::{WsClient, WsClientBuilder},
+};
+use node_template_runtime::{AccountId, Call, Event, Hash, Header, Index, UncheckedExtrinsic};
+use serde::Deserialize;
+use sp_core::{sr25519, storage::StorageKey, Bytes, Pair};
+use std::{
+	collections::HashMap,
+	fmt,
+	sync::Mutex,
+	time::{Duration, Instant},
+};
+
+/// How long a checkpoint is used for mortal transactions before the finalized head is read again.
+const CHECKPOINT_TTL: Duration = Duration::from_secs(60);
+
+/// Why a request to the node failed.
+#[derive(Debug)]
+pub enum Error {
+	/// The connection or the RPC failed, or the node rejected the transaction.
+	Rpc(jsonrpsee::core::Error),
+	/// The node doesn't know a block it should.
+	UnknownBlock(String),
+	/// A value read from the node doesn't decode.
+	Decode(codec::Error),
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Error::Rpc(e) => write!(f, "RPC error: {}", e),
+			Error::UnknownBlock(block) => write!(f, "Unknown block: {}", block),
+			Error::Decode(e) => write!(f, "Undecodable value: {}", e),
+		}
+	}
+}
+
+impl std::error::Error for Error {}
+
+impl From<jsonrpsee::core::Error> for Error {
+	fn from(e: jsonrpsee::core::Error) -> Self {
+		Error::Rpc(e)
+	}
+}
+
+impl From<codec::Error> for Error {
+	fn from(e: codec::Error) -> Self {
+		Error::Decode(e)
+	}
+}
+
+/// The versions a transaction is signed for, from `state_getRuntimeVersion`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RuntimeVersion {
+	spec_version: u32,
+	transaction_version: u32,
+}
+
+/// The extrinsics of a block, from `chain_getBlock`.
+#[derive(Deserialize)]
+struct SignedBlock {
+	block: BlockBody,
+}
+
+#[derive(Deserialize)]
+struct BlockBody {
+	extrinsics: Vec<Bytes>,
+}
+
+/// A connection to a node, signing transactions for its chain.
+pub struct Client {
+	rpc: WsClient,
+	chain: Mutex<ChainContext>,
+	mortality: Mortality,
+	nonces: Mutex<HashMap<AccountId, Index>>,
+	checkpoint: Mutex<Option<(Checkpoint, Instant)>>,
+}
+
+impl Client {
+	/// Connect to the node at `url`, `ws://` or `wss://`, and read what transactions commit to.
+	/// Transactions are mortal with the default period.
+	pub async fn connect(url: &str) -> Result<Self, Error> {
+		let rpc = WsClientBuilder::default().build(url).await?;
+		let genesis_hash: Option<Hash> = rpc.request("chain_getBlockHash", rpc_params![0]).await?;
+		let genesis_hash = genesis_hash.ok_or_else(|| Error::UnknownBlock("genesis".into()))?;
+		let version: RuntimeVersion = rpc.request("state_getRuntimeVersion", rpc_params![]).await?;
+
+		Ok(Client {
+			rpc,
+			chain: Mutex::new(ChainContext {
+				genesis_hash,
+				spec_version: version.spec_version,
+				transaction_version: version.transaction_version,
+			}),
+			mortality: Mortality::default(),
+			nonces: Default::default(),
+			checkpoint: Default::default(),
+		})
+	}
+
+	/// Sign the next transactions with `mortality`.
+	pub fn with_mortality(mut self, mortality: Mortality) -> Self {
+		self.mortality = mortality;
+		self
+	}
+
+	/// The chain the transactions are signed for.
+	pub fn chain(&self) -> ChainContext {
+		*self.chain.lock().expect("Chain lock is never poisoned; qed")
+	}
+
+	/// Read the runtime version of the best block again, to sign the next transactions for it.
+	pub async fn refresh_runtime_version(&self) -> Result<(), Error> {
+		let version: RuntimeVersion =
+			self.rpc.request("state_getRuntimeVersion", rpc_params![]).await?;
+		let mut chain = self.chain.lock().expect("Chain lock is never poisoned; qed");
+		chain.spec_version = version.spec_version;
+		chain.transaction_version = version.transaction_version;
+		Ok(())
+	}
+
+	/// The nonce of the next transaction of `account`, as the node counts it: included and pooled
+	/// transactions.
+	pub async fn account_next_index(&self, account: &AccountId) -> Result<Index, Error> {
+		Ok(self.rpc.request("system_accountNextIndex", rpc_params![account]).await?)
+	}
+
+	/// Forget the nonce of `account`, to read it from the node on its next transaction.
+	pub fn reset_nonce(&self, account: &AccountId) {
+		self.nonces.lock().expect("Nonces lock is never poisoned; qed").remove(account);
+	}
+
+	/// Take the next nonce of `account`.
+	async fn next_nonce(&self, account: &AccountId) -> Result<Index, Error> {
+		let cached = self
+			.nonces
+			.lock()
+			.expect("Nonces lock is never poisoned; qed")
+			.get(account)
+			.copied();
+		let from_node = match cached {
+			Some(_) => None,
+			None => Some(self.account_next_index(account).await?),
+		};
+
+		let mut nonces = self.nonces.lock().expect("Nonces lock is never poisoned; qed");
+		let nonce = nonces.entry(account.clone()).or_insert_with(|| from_node.unwrap_or_default());
+		let next = *nonce;
+		*nonce += 1;
+		Ok(next)
+	}
+
+	/// The start of the lifetime of mortal transactions: the finalized head, read again every
+	/// minute.
+	async fn checkpoint(&self) -> Result<Checkpoint, Error> {
+		let cached = *self.checkpoint.lock().expect("Checkpoint lock is never poisoned; qed");
+		if let Some((checkpoint, read_at)) = cached {
+			if read_at.elapsed() < CHECKPOINT_TTL {
+				return Ok(checkpoint)
+			}
+		}
+
+		let hash: Hash = self.rpc.request("chain_getFinalizedHead", rpc_params![]).await?;
+		let header: Option<Header> = self.rpc.request("chain_getHeader", rpc_params![hash]).await?;
+		let header = header.ok_or_else(|| Error::UnknownBlock(format!("{}", hash)))?;
+		let checkpoint = Checkpoint { number: header.number, hash };
+		*self.checkpoint.lock().expect("Checkpoint lock is never poisoned; qed") =
+			Some((checkpoint, Instant::now()));
+		Ok(checkpoint)
+	}
+
+	/// Sign `call` with the next nonce of `signer`.
+	pub async fn sign(
+		&self,
+		signer: &sr25519::Pair,
+		call: impl Into<Call>,
+	) -> Result<UncheckedExtrinsic, Error> {
+		let account = AccountId::from(signer.public());
+		let chain = self.chain();
+		let checkpoint = match self.mortality {
+			Mortality::Immortal => Checkpoint { number: 0, hash: chain.genesis_hash },
+			Mortality::Mortal { .. } => self.checkpoint().await?,
+		};
+		let nonce = self.next_nonce(&account).await?;
+		Ok(sign(signer, call.into(), nonce, self.mortality, &chain, &checkpoint))
+	}
+
+	/// Sign `call` and submit it to the transaction pool of the node.
+	pub async fn submit(
+		&self,
+		signer: &sr25519::Pair,
+		call: impl Into<Call>,
+	) -> Result<Hash, Error> {
+		let xt = self.sign(signer, call).await?;
+		let result = self
+			.rpc
+			.request("author_submitExtrinsic", rpc_params![Bytes(xt.encode())])
+			.await;
+		self.recover_on_error(signer, result).await
+	}
+
+	/// Sign `call`, submit it and follow it through the pool until it is finalized or dropped.
+	pub async fn submit_and_watch(
+		&self,
+		signer: &sr25519::Pair,
+		call: impl Into<Call>,
+	) -> Result<Subscription<TransactionStatus<Hash, Hash>>, Error> {
+		let xt = self.sign(signer, call).await?;
+		self.watch(signer, &xt).await
+	}
+
+	/// Submit `xt`, signed by `signer` with [`Client::sign`], and follow it through the pool until
+	/// it is finalized or dropped.
+	pub async fn watch(
+		&self,
+		signer: &sr25519::Pair,
+		xt: &UncheckedExtrinsic,
+	) -> Result<Subscription<TransactionStatus<Hash, Hash>>, Error> {
+		let result = self
+			.rpc
+			.subscribe(
+				"author_submitAndWatchExtrinsic",
+				rpc_params![Bytes(xt.encode())],
+				"author_unwatchExtrinsic",
+			)
+			.await;
+		self.recover_on_error(signer, result).await
+	}
+
+	/// The raw value at `key` in the state of the block `at`, the best block by default.
+	pub async fn storage(&self, key: Vec<u8>, at: Option<Hash>) -> Result<Option<Vec<u8>>, Error> {
+		let value: Option<Bytes> =
+			self.rpc.request("state_getStorage", rpc_params![StorageKey(key), at]).await?;
+		Ok(value.map(|value| value.0))
+	}
+
+	/// The DNA of the kitties of `owner` in the best block.
+	pub async fn kitties_owned(&self, owner: &AccountId) -> Result<Vec<[u8; 16]>, Error> {
+		match self.storage(keys::kitties_owned(owner), None).await? {
+			Some(value) => Ok(Decode::decode(&mut &value[..])?),
+			None => Ok(Vec::new()),
+		}
+	}
+
+	/// The events deposited by `xt` in the block `block`, none if the block doesn't include it.
+	pub async fn extrinsic_events(
+		&self,
+		block: Hash,
+		xt: &UncheckedExtrinsic,
+	) -> Result<Vec<Event>, Error> {
+		let signed: Option<SignedBlock> =
+			self.rpc.request("chain_getBlock", rpc_params![block]).await?;
+		let signed = signed.ok_or_else(|| Error::UnknownBlock(format!("{}", block)))?;
+		let encoded = xt.encode();
+		let index = match signed.block.extrinsics.iter().position(|included| included.0 == encoded)
+		{
+			Some(index) => index as u32,
+			None => return Ok(Vec::new()),
+		};
+
+		let events = match self.storage(keys::system_events(), Some(block)).await? {
+			Some(value) => Vec::<frame_system::EventRecord<Event, Hash>>::decode(&mut &value[..])?,
+			None => Vec::new(),
+		};
+		Ok(events
+			.into_iter()
+			.filter(|record| record.phase == frame_system::Phase::ApplyExtrinsic(index))
+			.map(|record| record.event)
+			.collect())
+	}
+
+	/// A rejected transaction didn't use its nonce, and neither do those signed after it. It may
+	/// have been rejected for being signed for the runtime before an upgrade.
+	async fn recover_on_error<T>(
+		&self,
+		signer: &sr25519::Pair,
+		result: Result<T, jsonrpsee::core::Error>,
+	) -> Result<T, Error> {
+		match result {
+			Ok(value) => Ok(value),
+			Err(e) => {
+				self.reset_nonce(&AccountId::from(signer.public()));
+				// The rejection is what the caller needs to know: if the version can't be read
+				// either, the next failed submission tries again.
+				let _ = self.refresh_runtime_version().await;
+				Err(e.into())
+			},
+		}
+	}
+}
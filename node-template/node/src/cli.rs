@@ -1,4 +1,5 @@
 use sc_cli::RunCmd;
+use std::str::FromStr;
 
 #[derive(Debug, clap::Parser)]
 pub struct Cli {
@@ -7,6 +8,46 @@ pub struct Cli {
 
 	#[clap(flatten)]
 	pub run: RunCmd,
+
+	/// Seal blocks on demand instead of running Aura and GRANDPA, for development:
+	/// `instant` seals a block for every transaction, `manual` when `engine_createBlock` is called
+	/// and `interval=<ms>` at a fixed pace.
+	#[clap(long)]
+	pub sealing: Option<Sealing>,
+
+	/// Index kitty and PoE events of finalized blocks into a database under the chain's directory
+	/// and serve them through the `indexer_*` RPCs.
+	#[clap(long)]
+	pub indexer: bool,
+}
+
+/// How blocks are sealed in development mode.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Sealing {
+	/// A block as soon as a transaction enters the pool, finalized right away.
+	Instant,
+	/// Blocks only through the `engine_createBlock` RPC.
+	Manual,
+	/// A block every given number of milliseconds, finalized right away.
+	Interval(u64),
+}
+
+impl FromStr for Sealing {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"instant" => Ok(Sealing::Instant),
+			"manual" => Ok(Sealing::Manual),
+			_ => match s.strip_prefix("interval=").map(u64::from_str) {
+				Some(Ok(millis)) if millis > 0 => Ok(Sealing::Interval(millis)),
+				_ => Err(format!(
+					"invalid sealing `{}`, expected `instant`, `manual` or `interval=<ms>`",
+					s
+				)),
+			},
+		}
+	}
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -18,6 +59,9 @@ pub enum Subcommand {
 	/// Build a chain specification.
 	BuildSpec(sc_cli::BuildSpecCmd),
 
+	/// Generate a raw chain specification from a description of the network.
+	GenerateSpec(crate::generate_spec::GenerateSpecCmd),
+
 	/// Validate blocks.
 	CheckBlock(sc_cli::CheckBlockCmd),
 
@@ -27,6 +71,21 @@ pub enum Subcommand {
 	/// Export the state of a given block into a chain spec.
 	ExportState(sc_cli::ExportStateCmd),
 
+	/// Inspect the kitties in the local database.
+	#[clap(subcommand)]
+	Kitties(crate::inspect::KittiesCmd),
+
+	/// Inspect the PoE claims in the local database.
+	#[clap(subcommand)]
+	Poe(crate::inspect::PoeCmd),
+
+	/// Send kitty and PoE transactions from many accounts to a dev node and report its throughput.
+	LoadTest(crate::load_test::LoadTestCmd),
+
+	/// Save the state of a block to a file, or start a new database from one.
+	#[clap(subcommand)]
+	Snapshot(crate::snapshot::SnapshotCmd),
+
 	/// Import blocks.
 	ImportBlocks(sc_cli::ImportBlocksCmd),
 
@@ -0,0 +1,187 @@
+//! Ethereum RPCs, compiled with the `evm` feature.
+//!
+//! Frontier keeps its own database mapping Ethereum block and transaction hashes to Substrate
+//! blocks. [`Frontier::new`] opens it and spawns the tasks filling it from imported blocks, and
+//! [`Frontier::merge_rpc`] adds the `eth_*`, `net_*` and `web3_*` methods served from it.
+
+use crate::service::{config_dir, FullBackend, FullClient};
+use fc_mapping_sync::{MappingSyncWorker, SyncStrategy};
+use fc_rpc::{
+	EthBlockDataCacheTask, EthTask, OverrideHandle, RuntimeApiStorageOverride, SchemaV1Override,
+	SchemaV2Override, SchemaV3Override, StorageOverride,
+};
+use fc_rpc_core::types::{FeeHistoryCache, FeeHistoryCacheLimit, FilterPool};
+use fp_storage::EthereumStorageSchema;
+use futures::StreamExt;
+use jsonrpsee::RpcModule;
+use node_template_runtime::{evm::TransactionConverter, opaque::Block, Hash};
+use sc_client_api::BlockchainEvents;
+use sc_network::NetworkService;
+use sc_service::{config::DatabaseSource, Configuration, TaskManager};
+use std::{
+	collections::BTreeMap,
+	sync::{Arc, Mutex},
+	time::Duration,
+};
+
+/// Filters untouched for this many blocks are dropped.
+const FILTER_RETAIN_THRESHOLD: u64 = 100;
+/// The most filters kept at once.
+const MAX_STORED_FILTERS: usize = 500;
+/// The most logs a single `eth_getLogs` returns.
+const MAX_PAST_LOGS: u32 = 10_000;
+/// The number of blocks `eth_feeHistory` can look back.
+const FEE_HISTORY_LIMIT: FeeHistoryCacheLimit = 2048;
+/// The number of blocks and of transaction status lists kept in memory.
+const BLOCK_DATA_CACHE_SIZE: usize = 50;
+
+/// The Frontier database and the caches the Ethereum RPCs share with the background tasks.
+pub struct Frontier {
+	backend: Arc<fc_db::Backend<Block>>,
+	filter_pool: FilterPool,
+	fee_history_cache: FeeHistoryCache,
+	overrides: Arc<OverrideHandle<Block>>,
+	block_data_cache: Arc<EthBlockDataCacheTask<Block>>,
+}
+
+impl Frontier {
+	/// Open the Frontier database under the chain's config directory and spawn the tasks keeping
+	/// it and the caches up to date.
+	pub fn new(
+		config: &Configuration,
+		task_manager: &TaskManager,
+		client: Arc<FullClient>,
+		backend: Arc<FullBackend>,
+	) -> Result<Self, String> {
+		let frontier_backend = Arc::new(fc_db::Backend::<Block>::new(&fc_db::DatabaseSettings {
+			source: DatabaseSource::RocksDb {
+				path: config_dir(config).join("frontier").join("db"),
+				cache_size: 0,
+			},
+		})?);
+
+		let filter_pool: FilterPool = Arc::new(Mutex::new(BTreeMap::new()));
+		let fee_history_cache: FeeHistoryCache = Arc::new(Mutex::new(BTreeMap::new()));
+		let overrides = overrides_handle(client.clone());
+		let block_data_cache = Arc::new(EthBlockDataCacheTask::new(
+			task_manager.spawn_handle(),
+			overrides.clone(),
+			BLOCK_DATA_CACHE_SIZE,
+			BLOCK_DATA_CACHE_SIZE,
+		));
+
+		let spawner = task_manager.spawn_essential_handle();
+		spawner.spawn(
+			"frontier-mapping-sync-worker",
+			Some("frontier"),
+			MappingSyncWorker::new(
+				client.import_notification_stream(),
+				Duration::new(6, 0),
+				client.clone(),
+				backend,
+				frontier_backend.clone(),
+				3,
+				0,
+				SyncStrategy::Normal,
+			)
+			.for_each(|()| futures::future::ready(())),
+		);
+		spawner.spawn(
+			"frontier-filter-pool",
+			Some("frontier"),
+			EthTask::filter_pool_task(client.clone(), filter_pool.clone(), FILTER_RETAIN_THRESHOLD),
+		);
+		spawner.spawn(
+			"frontier-fee-history",
+			Some("frontier"),
+			EthTask::fee_history_task(
+				client,
+				overrides.clone(),
+				fee_history_cache.clone(),
+				FEE_HISTORY_LIMIT,
+			),
+		);
+
+		Ok(Frontier {
+			backend: frontier_backend,
+			filter_pool,
+			fee_history_cache,
+			overrides,
+			block_data_cache,
+		})
+	}
+
+	/// Add the `eth_*`, `net_*` and `web3_*` RPCs to `module`.
+	pub fn merge_rpc(
+		&self,
+		module: &mut RpcModule<()>,
+		client: Arc<FullClient>,
+		pool: Arc<sc_transaction_pool::FullPool<Block, FullClient>>,
+		network: Arc<NetworkService<Block, Hash>>,
+		is_authority: bool,
+	) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+		use fc_rpc::{
+			Eth, EthApiServer, EthFilter, EthFilterApiServer, Net, NetApiServer, Web3,
+			Web3ApiServer,
+		};
+
+		module.merge(
+			Eth::new(
+				client.clone(),
+				pool.clone(),
+				pool.pool().clone(),
+				Some(TransactionConverter),
+				network.clone(),
+				// Accounts are never kept in the node, transactions arrive signed.
+				Vec::new(),
+				self.overrides.clone(),
+				self.backend.clone(),
+				is_authority,
+				self.block_data_cache.clone(),
+				self.fee_history_cache.clone(),
+				FEE_HISTORY_LIMIT,
+			)
+			.into_rpc(),
+		)?;
+		module.merge(
+			EthFilter::new(
+				client.clone(),
+				self.backend.clone(),
+				self.filter_pool.clone(),
+				MAX_STORED_FILTERS,
+				MAX_PAST_LOGS,
+				self.block_data_cache.clone(),
+			)
+			.into_rpc(),
+		)?;
+		module.merge(Net::new(client.clone(), network, true).into_rpc())?;
+		module.merge(Web3::new(client).into_rpc())?;
+
+		Ok(())
+	}
+}
+
+/// Read Ethereum blocks, receipts and statuses straight from storage for every storage schema,
+/// falling back to the runtime API.
+fn overrides_handle(client: Arc<FullClient>) -> Arc<OverrideHandle<Block>> {
+	type StorageOverrideBox = Box<dyn StorageOverride<Block> + Send + Sync>;
+
+	let mut overrides_map = BTreeMap::new();
+	overrides_map.insert(
+		EthereumStorageSchema::V1,
+		Box::new(SchemaV1Override::new(client.clone())) as StorageOverrideBox,
+	);
+	overrides_map.insert(
+		EthereumStorageSchema::V2,
+		Box::new(SchemaV2Override::new(client.clone())) as StorageOverrideBox,
+	);
+	overrides_map.insert(
+		EthereumStorageSchema::V3,
+		Box::new(SchemaV3Override::new(client.clone())) as StorageOverrideBox,
+	);
+
+	Arc::new(OverrideHandle {
+		schemas: overrides_map,
+		fallback: Box::new(RuntimeApiStorageOverride::new(client)),
+	})
+}
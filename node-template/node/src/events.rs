@@ -0,0 +1,25 @@
+//! The runtime events of a block, read from its state by the node tasks following app activity.
+
+use codec::Decode;
+use node_template_client::keys;
+use node_template_runtime::{opaque::Block, Event, Hash};
+use sc_client_api::{Backend, StorageProvider};
+use sp_core::storage::StorageKey;
+use sp_runtime::generic::BlockId;
+
+/// An event of the runtime with the phase of the block it was deposited in.
+pub type EventRecord = frame_system::EventRecord<Event, Hash>;
+
+/// The events deposited in the block `hash`. Fails once the block's state is pruned.
+pub fn block_events<C, B>(client: &C, hash: Hash) -> sp_blockchain::Result<Vec<EventRecord>>
+where
+	C: StorageProvider<Block, B>,
+	B: Backend<Block>,
+{
+	match client.storage(&BlockId::Hash(hash), &StorageKey(keys::system_events()))? {
+		Some(data) => Vec::<EventRecord>::decode(&mut &data.0[..]).map_err(|e| {
+			sp_blockchain::Error::Backend(format!("Invalid events in block {}: {}", hash, e))
+		}),
+		None => Ok(Vec::new()),
+	}
+}
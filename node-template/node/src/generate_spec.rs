@@ -0,0 +1,354 @@
+//! The `generate-spec` subcommand: a raw chain spec from a description of the network, checked
+//! against the runtime's limits, instead of hand-edited JSON.
+//!
+//! The description is TOML, or JSON when the file ends in `.json`. Accounts and session keys are
+//! SS58 addresses, DNAs and claims `0x` prefixed hex:
+//!
+//! ```toml
+//! name = "Kitty Testnet"
+//! id = "kitty_testnet"
+//! chain_type = "Live"
+//! boot_nodes = ["/dns/boot.kitties.example/tcp/30333/p2p/12D3KooW..."]
+//! sudo = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//! council = ["5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"]
+//!
+//! [[authorities]]
+//! account = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//! aura = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//! grandpa = "5FA9nQDVg267DEd8m1ZypXLBnvN7SFxYwV7ndqSYGiN9TTpu"
+//! im_online = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//!
+//! [[balances]]
+//! account = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//! balance = "1000000000000000000"
+//!
+//! [[kitties]]
+//! owner = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//! dna = "0x00112233445566778899aabbccddeeff"
+//! gender = "Female"
+//!
+//! [[claims]]
+//! owner = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+//! claim = "0x6465656421"
+//! ```
+
+use crate::chain_spec::{self, ChainSpec};
+use frame_support::traits::Get;
+use node_template_runtime::{
+	AccountId, Balance, CouncilMaxMembers, GenesisConfig, ImOnlineId, Runtime, WASM_BINARY,
+};
+use pallet_kitties::Gender;
+use sc_service::{config::MultiaddrWithPeerId, ChainType, Properties};
+use serde::Deserialize;
+use sp_consensus_aura::sr25519::AuthorityId as AuraId;
+use sp_core::Bytes;
+use sp_finality_grandpa::AuthorityId as GrandpaId;
+use std::{collections::BTreeSet, fmt::Debug, fs, path::PathBuf};
+
+/// Generate a raw chain spec from a description of the network.
+#[derive(Debug, clap::Parser)]
+pub struct GenerateSpecCmd {
+	/// The description of the network: authorities, balances, sudo key, council, kitties and
+	/// claims. TOML, or JSON for a `.json` file.
+	#[clap(value_name = "DESCRIPTION")]
+	pub description: PathBuf,
+
+	/// Where to write the chain spec, instead of the standard output.
+	#[clap(long, short, value_name = "FILE")]
+	pub output: Option<PathBuf>,
+}
+
+impl GenerateSpecCmd {
+	pub fn run(&self) -> sc_cli::Result<()> {
+		let description = fs::read_to_string(&self.description)?;
+		let network: Network = if self.description.extension().map_or(false, |e| e == "json") {
+			serde_json::from_str(&description).map_err(|e| e.to_string())
+		} else {
+			toml::from_str(&description).map_err(|e| e.to_string())
+		}
+		.map_err(|e| invalid(format!("{}: {}", self.description.display(), e)))?;
+
+		network.validate().map_err(invalid)?;
+		let json = network.chain_spec().and_then(|spec| spec.as_json(true)).map_err(invalid)?;
+
+		match &self.output {
+			Some(path) => fs::write(path, json)?,
+			None => println!("{}", json),
+		}
+		Ok(())
+	}
+}
+
+fn invalid(message: String) -> sc_cli::Error {
+	sc_cli::Error::Input(message)
+}
+
+/// The description of a network.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Network {
+	pub name: String,
+	pub id: String,
+	#[serde(default = "live")]
+	pub chain_type: ChainType,
+	#[serde(default)]
+	pub protocol_id: Option<String>,
+	#[serde(default)]
+	pub boot_nodes: Vec<MultiaddrWithPeerId>,
+	#[serde(default)]
+	pub properties: Option<Properties>,
+	pub authorities: Vec<Authority>,
+	#[serde(default)]
+	pub sudo: Option<AccountId>,
+	#[serde(default)]
+	pub council: Vec<AccountId>,
+	#[serde(default)]
+	pub balances: Vec<Endowment>,
+	#[serde(default)]
+	pub kitties: Vec<GenesisKitty>,
+	#[serde(default)]
+	pub claims: Vec<GenesisClaim>,
+}
+
+fn live() -> ChainType {
+	ChainType::Live
+}
+
+/// A validator of the first session and its session keys.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Authority {
+	pub account: AccountId,
+	pub aura: AuraId,
+	pub grandpa: GrandpaId,
+	pub im_online: ImOnlineId,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Endowment {
+	pub account: AccountId,
+	pub balance: Amount,
+}
+
+/// A balance, as a string when it doesn't fit the integers of TOML.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Amount {
+	Number(u64),
+	Text(String),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GenesisKitty {
+	pub owner: AccountId,
+	pub dna: Bytes,
+	pub gender: Gender,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GenesisClaim {
+	pub owner: AccountId,
+	pub claim: Bytes,
+}
+
+impl Amount {
+	fn balance(&self) -> Result<Balance, String> {
+		match self {
+			Amount::Number(number) => Ok(*number as Balance),
+			Amount::Text(text) =>
+				text.parse().map_err(|_| format!("Invalid balance {}, expected an integer", text)),
+		}
+	}
+}
+
+impl Network {
+	/// Check the description against the limits the runtime enforces, which would otherwise make
+	/// the genesis fail to build.
+	pub fn validate(&self) -> Result<(), String> {
+		let min_validators = <Runtime as pallet_validator_set::Config>::MinAuthorities::get();
+		let max_validators = <Runtime as pallet_validator_set::Config>::MaxValidators::get();
+		let existential_deposit = <Runtime as pallet_balances::Config>::ExistentialDeposit::get();
+		let max_owned = <Runtime as pallet_kitties::Config>::MaxKittiesOwned::get() as usize;
+		let claim_limit = <Runtime as pallet_poe::Config>::ClaimLimitSize::get() as usize;
+
+		if self.id.is_empty() {
+			return Err("The chain id can't be empty".into())
+		}
+		let validators = self.authorities.len() as u32;
+		if validators < min_validators || validators > max_validators {
+			return Err(format!(
+				"{} authorities, expected between {} and {}",
+				validators, min_validators, max_validators
+			))
+		}
+		unique("authority", self.authorities.iter().map(|a| &a.account))?;
+
+		if self.council.len() as u32 > CouncilMaxMembers::get() {
+			return Err(format!("The council has more than {} members", CouncilMaxMembers::get()))
+		}
+		unique("council member", &self.council)?;
+
+		for endowment in &self.balances {
+			if endowment.balance.balance()? < existential_deposit {
+				return Err(format!(
+					"The balance of {} is below the existential deposit of {}",
+					endowment.account, existential_deposit
+				))
+			}
+		}
+		unique("endowed account", self.balances.iter().map(|e| &e.account))?;
+
+		for kitty in &self.kitties {
+			if kitty.dna.len() != 16 {
+				return Err(format!("The DNA of a kitty of {} isn't 16 bytes", kitty.owner))
+			}
+			if self.kitties.iter().filter(|k| k.owner == kitty.owner).count() > max_owned {
+				return Err(format!("{} owns more than {} kitties", kitty.owner, max_owned))
+			}
+		}
+		unique("kitty DNA", self.kitties.iter().map(|k| &k.dna.0))?;
+
+		for claim in &self.claims {
+			if claim.claim.len() > claim_limit {
+				let owner = &claim.owner;
+				return Err(format!("A claim of {} is longer than {} bytes", owner, claim_limit))
+			}
+		}
+		unique("claim", self.claims.iter().map(|c| &c.claim.0))?;
+
+		Ok(())
+	}
+
+	/// The chain spec of the network, with the runtime of this node.
+	pub fn chain_spec(&self) -> Result<ChainSpec, String> {
+		let wasm_binary = WASM_BINARY.ok_or_else(|| "Wasm binary not available".to_string())?;
+		let authorities: Vec<_> = self
+			.authorities
+			.iter()
+			.map(|a| (a.account.clone(), a.aura.clone(), a.grandpa.clone(), a.im_online.clone()))
+			.collect();
+		let balances = self
+			.balances
+			.iter()
+			.map(|e| Ok((e.account.clone(), e.balance.balance()?)))
+			.collect::<Result<Vec<_>, String>>()?;
+		let kitties: Vec<_> = self
+			.kitties
+			.iter()
+			.map(|k| {
+				let dna = k.dna[..].try_into().expect("DNA length is validated; qed");
+				(k.owner.clone(), dna, k.gender)
+			})
+			.collect();
+		let claims: Vec<_> =
+			self.claims.iter().map(|c| (c.owner.clone(), c.claim.0.clone())).collect();
+		let (sudo, council) = (self.sudo.clone(), self.council.clone());
+
+		Ok(ChainSpec::from_genesis(
+			&self.name,
+			&self.id,
+			self.chain_type.clone(),
+			move || -> GenesisConfig {
+				chain_spec::genesis(
+					wasm_binary,
+					authorities.clone(),
+					sudo.clone(),
+					council.clone(),
+					balances.clone(),
+					kitties.clone(),
+					claims.clone(),
+				)
+			},
+			self.boot_nodes.clone(),
+			None,
+			self.protocol_id.as_deref(),
+			None,
+			self.properties.clone(),
+			None,
+		))
+	}
+}
+
+/// Fails on the first item found twice.
+fn unique<'a, T: Ord + Debug + 'a>(
+	what: &str,
+	items: impl IntoIterator<Item = &'a T>,
+) -> Result<(), String> {
+	let mut seen = BTreeSet::new();
+	for item in items {
+		if !seen.insert(item) {
+			return Err(format!("Duplicate {} {:?}", what, item))
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+	const GRANDPA: &str = "5FA9nQDVg267DEd8m1ZypXLBnvN7SFxYwV7ndqSYGiN9TTpu";
+
+	fn network(extra: &str) -> Network {
+		let description = format!(
+			r#"
+			name = "Kitty Testnet"
+			id = "kitty_testnet"
+			{extra}
+
+			[[authorities]]
+			account = "{ALICE}"
+			aura = "{ALICE}"
+			grandpa = "{GRANDPA}"
+			im_online = "{ALICE}"
+
+			[[balances]]
+			account = "{ALICE}"
+			balance = "1000000000000000000000"
+			"#
+		);
+		toml::from_str(&description).unwrap()
+	}
+
+	#[test]
+	fn description_is_parsed_and_validated() {
+		let network = network(&format!(r#"sudo = "{ALICE}""#));
+
+		assert_eq!(network.validate(), Ok(()));
+		assert_eq!(network.chain_type, ChainType::Live);
+		assert_eq!(network.balances[0].balance.balance(), Ok(1_000_000_000_000_000_000_000));
+		assert_eq!(network.sudo, Some(network.authorities[0].account.clone()));
+	}
+
+	#[test]
+	fn runtime_limits_are_enforced() {
+		let mut network = network("");
+		network.balances[0].balance = Amount::Number(1);
+		assert!(network.validate().unwrap_err().contains("existential deposit"));
+
+		let mut network = self::network("");
+		let owner = network.authorities[0].account.clone();
+		let kitty = |dna: &[u8]| GenesisKitty {
+			owner: owner.clone(),
+			dna: dna.to_vec().into(),
+			gender: Gender::Male,
+		};
+		network.kitties = vec![kitty(&[1; 16]), kitty(&[1; 16])];
+		assert!(network.validate().unwrap_err().starts_with("Duplicate kitty DNA"));
+		network.kitties = vec![kitty(&[1; 15])];
+		assert!(network.validate().unwrap_err().contains("isn't 16 bytes"));
+
+		let mut network = self::network("");
+		let owner = network.authorities[0].account.clone();
+		network.claims = vec![GenesisClaim { owner, claim: vec![0; 17].into() }];
+		assert!(network.validate().unwrap_err().contains("longer than 16 bytes"));
+
+		let mut network = self::network("");
+		network.authorities.clear();
+		assert!(network.validate().unwrap_err().contains("0 authorities"));
+	}
+}
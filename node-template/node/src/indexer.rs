@@ -0,0 +1,496 @@
+//! An index of kitty and PoE events kept in an embedded database next to the chain, so front ends
+//! can query ownership and sale history instead of scanning every block. Enabled with `--indexer`,
+//! served by the `indexer_*` RPCs.
+//!
+//! Only finalized blocks are indexed, so the history never has to be rolled back after a reorg.
+//! Missed blocks are caught up on start, which needs their state: run an archive node to index a
+//! chain from genesis.
+
+use crate::{
+	events::{block_events, EventRecord},
+	service::FullClient,
+};
+use futures::StreamExt;
+use jsonrpsee::{
+	core::{Error as JsonRpseeError, RpcResult},
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use node_template_runtime::{AccountId, Balance, BlockNumber, Event, Hash};
+use sc_client_api::BlockchainEvents;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sp_blockchain::HeaderBackend;
+use sp_core::{Bytes, H256};
+use std::{fmt, path::Path, sync::Arc};
+
+/// Key of the number of the last indexed block.
+const LAST_INDEXED: &[u8] = b"last_indexed";
+
+/// An event found in a block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Indexed<E> {
+	pub block_number: BlockNumber,
+	pub block_hash: Hash,
+	/// The position of the event among those of the block.
+	pub event_index: u32,
+	pub event: E,
+}
+
+/// An event in the life of a kitty.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KittyEvent {
+	Created { owner: AccountId },
+	PriceSet { price: Option<Balance> },
+	Transferred { from: AccountId, to: AccountId },
+	Sold { seller: AccountId, buyer: AccountId, price: Balance },
+}
+
+/// A kitty sale, indexed for both the seller and the buyer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sale {
+	pub kitty: Bytes,
+	pub seller: AccountId,
+	pub buyer: AccountId,
+	pub price: Balance,
+}
+
+/// A change to the claims of an account. Transfers are indexed for both accounts.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ClaimEvent {
+	Created { claim: Bytes },
+	BatchCreated { root: H256 },
+	Revoked { claim: Bytes },
+	RevokedByAuthority { claim: Bytes },
+	Transferred { from: AccountId, to: AccountId, claim: Bytes },
+}
+
+/// Why indexing or a query failed.
+#[derive(Debug)]
+pub enum Error {
+	/// The database failed.
+	Database(sled::Error),
+	/// A block or its events couldn't be read.
+	Client(sp_blockchain::Error),
+	/// An entry of the database doesn't decode.
+	Corrupted(serde_json::Error),
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Error::Database(e) => write!(f, "Indexer database error: {}", e),
+			Error::Client(e) => write!(f, "Reading blocks failed: {}", e),
+			Error::Corrupted(e) => write!(f, "Corrupted indexer entry: {}", e),
+		}
+	}
+}
+
+impl From<sled::Error> for Error {
+	fn from(e: sled::Error) -> Self {
+		Error::Database(e)
+	}
+}
+
+impl From<sp_blockchain::Error> for Error {
+	fn from(e: sp_blockchain::Error) -> Self {
+		Error::Client(e)
+	}
+}
+
+/// The indexed events, in a tree per query. Entries are keyed by the kitty or account they are
+/// looked up with, followed by the block number and event index, so a prefix scan returns them
+/// oldest first.
+pub struct Indexer {
+	db: sled::Db,
+	kitties: sled::Tree,
+	sales: sled::Tree,
+	claims: sled::Tree,
+}
+
+impl Indexer {
+	/// Open or create the database at `path`.
+	pub fn open(path: &Path) -> Result<Self, Error> {
+		let db = sled::open(path)?;
+		Ok(Indexer {
+			kitties: db.open_tree("kitties")?,
+			sales: db.open_tree("sales")?,
+			claims: db.open_tree("claims")?,
+			db,
+		})
+	}
+
+	/// The number of the last indexed block.
+	pub fn last_indexed(&self) -> Result<Option<BlockNumber>, Error> {
+		Ok(self
+			.db
+			.get(LAST_INDEXED)?
+			.and_then(|number| number.as_ref().try_into().ok())
+			.map(BlockNumber::from_be_bytes))
+	}
+
+	/// Index the events of a block. Indexing a block again overwrites its entries, so a block
+	/// interrupted by a crash is simply indexed again.
+	pub fn index_block(
+		&self,
+		number: BlockNumber,
+		hash: Hash,
+		events: Vec<EventRecord>,
+	) -> Result<(), Error> {
+		let mut kitties = sled::Batch::default();
+		let mut sales = sled::Batch::default();
+		let mut claims = sled::Batch::default();
+
+		for (index, record) in events.into_iter().enumerate() {
+			let at = At { number, hash, index: index as u32 };
+			match record.event {
+				Event::SubstrateKitties(event) => match event {
+					pallet_kitties::Event::Created { kitty, owner } => {
+						let created = at.event(KittyEvent::Created { owner });
+						insert(&mut kitties, &kitty, &created);
+					},
+					pallet_kitties::Event::PriceSet { kitty, price } => {
+						let price_set = at.event(KittyEvent::PriceSet { price });
+						insert(&mut kitties, &kitty, &price_set);
+					},
+					pallet_kitties::Event::Transferred { from, to, kitty } => {
+						let transferred = at.event(KittyEvent::Transferred { from, to });
+						insert(&mut kitties, &kitty, &transferred);
+					},
+					pallet_kitties::Event::Sold { seller, buyer, kitty, price } => {
+						let sold = KittyEvent::Sold {
+							seller: seller.clone(),
+							buyer: buyer.clone(),
+							price,
+						};
+						insert(&mut kitties, &kitty, &at.event(sold));
+						let sale = Sale { kitty: kitty.to_vec().into(), seller, buyer, price };
+						let sale = at.event(sale);
+						insert(&mut sales, sale.event.seller.as_ref(), &sale);
+						insert(&mut sales, sale.event.buyer.as_ref(), &sale);
+					},
+					_ => {},
+				},
+				Event::PoeModule(event) => match event {
+					pallet_poe::Event::ClaimCreated(owner, claim) => {
+						let claim = claim.to_vec().into();
+						let created = at.event(ClaimEvent::Created { claim });
+						insert(&mut claims, owner.as_ref(), &created);
+					},
+					pallet_poe::Event::BatchClaimCreated(owner, root) => {
+						let created = at.event(ClaimEvent::BatchCreated { root: H256(root) });
+						insert(&mut claims, owner.as_ref(), &created);
+					},
+					pallet_poe::Event::ClaimRevoked(owner, claim) => {
+						let claim = claim.to_vec().into();
+						let revoked = at.event(ClaimEvent::Revoked { claim });
+						insert(&mut claims, owner.as_ref(), &revoked);
+					},
+					pallet_poe::Event::ClaimRevokedByAuthority(owner, claim) => {
+						let claim = claim.to_vec().into();
+						let revoked = at.event(ClaimEvent::RevokedByAuthority { claim });
+						insert(&mut claims, owner.as_ref(), &revoked);
+					},
+					pallet_poe::Event::ClaimTransferred(from, to, claim) => {
+						let transferred = at.event(ClaimEvent::Transferred {
+							from: from.clone(),
+							to: to.clone(),
+							claim: claim.to_vec().into(),
+						});
+						insert(&mut claims, from.as_ref(), &transferred);
+						insert(&mut claims, to.as_ref(), &transferred);
+					},
+					_ => {},
+				},
+				_ => {},
+			}
+		}
+
+		self.kitties.apply_batch(kitties)?;
+		self.sales.apply_batch(sales)?;
+		self.claims.apply_batch(claims)?;
+		self.db.insert(LAST_INDEXED, &number.to_be_bytes()[..])?;
+		Ok(())
+	}
+
+	/// Index the finalized blocks following the last indexed one.
+	pub fn catch_up(&self, client: &FullClient) -> Result<(), Error> {
+		let finalized = client.info().finalized_number;
+		let next = self.last_indexed()?.map_or(0, |number| number + 1);
+		for number in next..=finalized {
+			let hash = client.hash(number)?.ok_or_else(|| {
+				sp_blockchain::Error::UnknownBlock(format!("Finalized block {}", number))
+			})?;
+			self.index_block(number, hash, block_events(client, hash)?)?;
+		}
+		Ok(())
+	}
+
+	/// Every event of the kitty with DNA `kitty`, oldest first.
+	pub fn kitty_history(&self, kitty: &[u8; 16]) -> Result<Vec<Indexed<KittyEvent>>, Error> {
+		scan(&self.kitties, kitty)
+	}
+
+	/// The sales `account` was the seller or the buyer of, oldest first.
+	pub fn sales_by_account(&self, account: &AccountId) -> Result<Vec<Indexed<Sale>>, Error> {
+		scan(&self.sales, account.as_ref())
+	}
+
+	/// The claims `owner` created, revoked, received or gave away, oldest first.
+	pub fn claims_by_owner(&self, owner: &AccountId) -> Result<Vec<Indexed<ClaimEvent>>, Error> {
+		scan(&self.claims, owner.as_ref())
+	}
+}
+
+/// Where an event was found.
+struct At {
+	number: BlockNumber,
+	hash: Hash,
+	index: u32,
+}
+
+impl At {
+	fn event<E>(&self, event: E) -> Indexed<E> {
+		Indexed { block_number: self.number, block_hash: self.hash, event_index: self.index, event }
+	}
+}
+
+fn insert<E: Serialize>(batch: &mut sled::Batch, prefix: &[u8], indexed: &Indexed<E>) {
+	let key = [prefix, &indexed.block_number.to_be_bytes(), &indexed.event_index.to_be_bytes()];
+	let value = serde_json::to_vec(indexed).expect("Indexed events always serialize; qed");
+	batch.insert(key.concat(), value);
+}
+
+fn scan<E: DeserializeOwned>(tree: &sled::Tree, prefix: &[u8]) -> Result<Vec<Indexed<E>>, Error> {
+	tree.scan_prefix(prefix)
+		.values()
+		.map(|value| serde_json::from_slice(&value?).map_err(Error::Corrupted))
+		.collect()
+}
+
+/// Index every block finalized until the node stops. Reading blocks and writing the database
+/// block the thread, so this is spawned as a blocking task.
+///
+/// Indexing stops for good when a block can't be read, e.g. because its state was pruned, as it
+/// would fail again on every new block. Database errors are retried on the next block.
+pub async fn run(indexer: Arc<Indexer>, client: Arc<FullClient>) {
+	let mut finality_notifications = client.finality_notification_stream();
+	loop {
+		match indexer.catch_up(&client) {
+			Ok(()) => {},
+			Err(e @ Error::Client(_)) => {
+				log::error!(
+					target: "indexer",
+					"Indexing stopped: {}. Index a chain from genesis on an archive node.",
+					e,
+				);
+				return
+			},
+			Err(e) => {
+				log::error!(target: "indexer", "Indexing failed, retrying on the next block: {}", e)
+			},
+		}
+		if finality_notifications.next().await.is_none() {
+			return
+		}
+	}
+}
+
+/// Queries of the indexed kitty and PoE events.
+#[rpc(server, namespace = "indexer")]
+pub trait IndexerApi {
+	/// Every event of a kitty, given its DNA, oldest first.
+	#[method(name = "kittyHistory")]
+	fn kitty_history(&self, kitty: Bytes) -> RpcResult<Vec<Indexed<KittyEvent>>>;
+
+	/// The sales an account was the seller or the buyer of, oldest first.
+	#[method(name = "salesByAccount")]
+	fn sales_by_account(&self, account: AccountId) -> RpcResult<Vec<Indexed<Sale>>>;
+
+	/// The claims an account created, revoked, received or gave away, oldest first.
+	#[method(name = "claimsByOwner")]
+	fn claims_by_owner(&self, owner: AccountId) -> RpcResult<Vec<Indexed<ClaimEvent>>>;
+}
+
+/// The `indexer_*` RPCs.
+pub struct IndexerRpc {
+	indexer: Arc<Indexer>,
+}
+
+impl IndexerRpc {
+	pub fn new(indexer: Arc<Indexer>) -> Self {
+		IndexerRpc { indexer }
+	}
+}
+
+/// The error code of failed queries.
+const QUERY_ERROR: i32 = 1;
+/// The error code of invalid kitty DNAs.
+const INVALID_KITTY: i32 = 2;
+
+fn query_error(e: Error) -> JsonRpseeError {
+	CallError::Custom(ErrorObject::owned(QUERY_ERROR, "Indexer query failed", Some(e.to_string())))
+		.into()
+}
+
+impl IndexerApiServer for IndexerRpc {
+	fn kitty_history(&self, kitty: Bytes) -> RpcResult<Vec<Indexed<KittyEvent>>> {
+		let kitty: [u8; 16] = kitty.0.try_into().map_err(|_| {
+			CallError::Custom(ErrorObject::owned(
+				INVALID_KITTY,
+				"A kitty DNA is 16 bytes",
+				None::<()>,
+			))
+		})?;
+		self.indexer.kitty_history(&kitty).map_err(query_error)
+	}
+
+	fn sales_by_account(&self, account: AccountId) -> RpcResult<Vec<Indexed<Sale>>> {
+		self.indexer.sales_by_account(&account).map_err(query_error)
+	}
+
+	fn claims_by_owner(&self, owner: AccountId) -> RpcResult<Vec<Indexed<ClaimEvent>>> {
+		self.indexer.claims_by_owner(&owner).map_err(query_error)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_system::Phase;
+	use node_template_runtime::Runtime;
+	use sp_keyring::Sr25519Keyring::{Alice, Bob};
+
+	const DNA: [u8; 16] = [7; 16];
+
+	fn record(event: impl Into<Event>) -> EventRecord {
+		EventRecord { phase: Phase::ApplyExtrinsic(0), event: event.into(), topics: vec![] }
+	}
+
+	fn kitties(event: pallet_kitties::Event<Runtime>) -> EventRecord {
+		record(event)
+	}
+
+	fn poe(event: pallet_poe::Event<Runtime>) -> EventRecord {
+		record(event)
+	}
+
+	fn claim(bytes: &[u8]) -> pallet_poe::BoundedClaim<Runtime> {
+		bytes.to_vec().try_into().unwrap()
+	}
+
+	#[test]
+	fn kitty_history_and_sales_are_indexed() {
+		let dir = tempfile::tempdir().unwrap();
+		let indexer = Indexer::open(dir.path()).unwrap();
+		let (alice, bob) = (Alice.to_account_id(), Bob.to_account_id());
+
+		indexer
+			.index_block(
+				1,
+				Hash::repeat_byte(1),
+				vec![
+					kitties(pallet_kitties::Event::Created { kitty: DNA, owner: alice.clone() }),
+					kitties(pallet_kitties::Event::PriceSet { kitty: DNA, price: Some(10) }),
+				],
+			)
+			.unwrap();
+		indexer
+			.index_block(
+				2,
+				Hash::repeat_byte(2),
+				vec![
+					record(frame_system::Event::<Runtime>::ExtrinsicSuccess {
+						dispatch_info: Default::default(),
+					}),
+					kitties(pallet_kitties::Event::Sold {
+						seller: alice.clone(),
+						buyer: bob.clone(),
+						kitty: DNA,
+						price: 10,
+					}),
+				],
+			)
+			.unwrap();
+
+		let history = indexer.kitty_history(&DNA).unwrap();
+		assert_eq!(
+			history.iter().map(|indexed| &indexed.event).collect::<Vec<_>>(),
+			vec![
+				&KittyEvent::Created { owner: alice.clone() },
+				&KittyEvent::PriceSet { price: Some(10) },
+				&KittyEvent::Sold { seller: alice.clone(), buyer: bob.clone(), price: 10 },
+			]
+		);
+		assert_eq!((history[2].block_number, history[2].event_index), (2, 1));
+
+		let sale = Sale {
+			kitty: DNA.to_vec().into(),
+			seller: alice.clone(),
+			buyer: bob.clone(),
+			price: 10,
+		};
+		assert_eq!(indexer.sales_by_account(&alice).unwrap()[0].event, sale);
+		assert_eq!(indexer.sales_by_account(&bob).unwrap()[0].event, sale);
+		assert_eq!(indexer.last_indexed().unwrap(), Some(2));
+	}
+
+	#[test]
+	fn claims_are_indexed_by_owner() {
+		let dir = tempfile::tempdir().unwrap();
+		let indexer = Indexer::open(dir.path()).unwrap();
+		let (alice, bob) = (Alice.to_account_id(), Bob.to_account_id());
+
+		indexer
+			.index_block(
+				1,
+				Hash::repeat_byte(1),
+				vec![
+					poe(pallet_poe::Event::ClaimCreated(alice.clone(), claim(b"deed"))),
+					poe(pallet_poe::Event::ClaimTransferred(
+						alice.clone(),
+						bob.clone(),
+						claim(b"deed"),
+					)),
+				],
+			)
+			.unwrap();
+
+		let transferred = ClaimEvent::Transferred {
+			from: alice.clone(),
+			to: bob.clone(),
+			claim: b"deed".to_vec().into(),
+		};
+		let events = |owner| {
+			indexer
+				.claims_by_owner(owner)
+				.unwrap()
+				.into_iter()
+				.map(|indexed| indexed.event)
+				.collect::<Vec<_>>()
+		};
+		assert_eq!(
+			events(&alice),
+			vec![ClaimEvent::Created { claim: b"deed".to_vec().into() }, transferred.clone()]
+		);
+		assert_eq!(events(&bob), vec![transferred]);
+	}
+
+	#[test]
+	fn reindexing_a_block_overwrites_it() {
+		let dir = tempfile::tempdir().unwrap();
+		let indexer = Indexer::open(dir.path()).unwrap();
+		let created = || {
+			let owner = Alice.to_account_id();
+			vec![kitties(pallet_kitties::Event::Created { kitty: DNA, owner })]
+		};
+
+		indexer.index_block(1, Hash::repeat_byte(1), created()).unwrap();
+		indexer.index_block(1, Hash::repeat_byte(1), created()).unwrap();
+
+		assert_eq!(indexer.kitty_history(&DNA).unwrap().len(), 1);
+	}
+}
@@ -0,0 +1,394 @@
+//! The `kitties` and `poe` subcommands: app state read straight from the local database, at the
+//! best block or at `--at`, without a running node.
+
+use crate::service::FullClient;
+use codec::Decode;
+use node_template_client::keys;
+use node_template_runtime::{opaque::Block, AccountId, Balance, BlockNumber, Runtime};
+use pallet_kitties::{Gender, Kitty};
+use sc_cli::{BlockNumberOrHash, CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use sc_client_api::StorageProvider;
+use serde::Serialize;
+use sp_blockchain::HeaderBackend;
+use sp_core::{bytes::from_hex, hexdisplay::HexDisplay, storage::StorageKey, Bytes};
+use sp_runtime::{generic::BlockId, Permill};
+use std::collections::BTreeSet;
+
+/// Where and how to read the state.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct InspectParams {
+	/// The block to read the state at, by number or hash. The best block by default.
+	#[clap(long, value_name = "BLOCK")]
+	pub at: Option<BlockNumberOrHash>,
+
+	/// How to print the results.
+	#[clap(long, arg_enum, default_value = "table")]
+	pub output: Format,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl CliConfiguration for InspectParams {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ArgEnum)]
+pub enum Format {
+	Table,
+	Json,
+}
+
+/// Inspect the kitties.
+#[derive(Debug, clap::Subcommand)]
+pub enum KittiesCmd {
+	/// List the kitties.
+	List {
+		/// Only the kitties with a price.
+		#[clap(long)]
+		for_sale: bool,
+		#[clap(flatten)]
+		params: InspectParams,
+	},
+	/// Show a kitty.
+	Show {
+		/// The DNA of the kitty, in hex.
+		#[clap(parse(try_from_str = parse_dna))]
+		dna: [u8; 16],
+		#[clap(flatten)]
+		params: InspectParams,
+	},
+	/// List the kitties of an account.
+	OwnedBy {
+		/// The owner, SS58 or hex.
+		account: AccountId,
+		#[clap(flatten)]
+		params: InspectParams,
+	},
+	/// Count the kitties, their owners and those for sale.
+	Stats {
+		#[clap(flatten)]
+		params: InspectParams,
+	},
+}
+
+/// Inspect the PoE claims.
+#[derive(Debug, clap::Subcommand)]
+pub enum PoeCmd {
+	/// Show the owner of a claim.
+	Show {
+		/// The claim, in hex.
+		#[clap(parse(try_from_str = from_hex))]
+		claim: Vec<u8>,
+		#[clap(flatten)]
+		params: InspectParams,
+	},
+	/// List the claims.
+	List {
+		/// Only the claims of this account, SS58 or hex.
+		#[clap(long)]
+		owner: Option<AccountId>,
+		#[clap(flatten)]
+		params: InspectParams,
+	},
+}
+
+fn parse_dna(dna: &str) -> Result<[u8; 16], String> {
+	from_hex(dna)
+		.map_err(|e| e.to_string())?
+		.try_into()
+		.map_err(|_| "A DNA is 16 bytes".to_string())
+}
+
+/// A kitty as printed.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct KittyRow {
+	dna: Bytes,
+	owner: AccountId,
+	gender: Gender,
+	price: Option<Balance>,
+}
+
+impl From<Kitty<Runtime>> for KittyRow {
+	fn from(kitty: Kitty<Runtime>) -> Self {
+		KittyRow {
+			dna: kitty.dna.to_vec().into(),
+			owner: kitty.owner,
+			gender: kitty.gender,
+			price: kitty.price,
+		}
+	}
+}
+
+impl Row for KittyRow {
+	const HEADER: &'static [&'static str] = &["DNA", "OWNER", "GENDER", "PRICE"];
+
+	fn cells(&self) -> Vec<String> {
+		vec![
+			format!("0x{}", HexDisplay::from(&self.dna.0)),
+			self.owner.to_string(),
+			format!("{:?}", self.gender),
+			self.price.map_or_else(|| "-".into(), |price| price.to_string()),
+		]
+	}
+}
+
+/// A claim as printed.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ClaimRow {
+	claim: Bytes,
+	owner: AccountId,
+	block: BlockNumber,
+}
+
+impl Row for ClaimRow {
+	const HEADER: &'static [&'static str] = &["CLAIM", "OWNER", "BLOCK"];
+
+	fn cells(&self) -> Vec<String> {
+		vec![
+			format!("0x{}", HexDisplay::from(&self.claim.0)),
+			self.owner.to_string(),
+			self.block.to_string(),
+		]
+	}
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct KittyStats {
+	kitties: u64,
+	owners: usize,
+	males: usize,
+	females: usize,
+	for_sale: usize,
+	floor_price: Option<Balance>,
+	marketplace_fee: Permill,
+}
+
+impl Row for KittyStats {
+	const HEADER: &'static [&'static str] =
+		&["KITTIES", "OWNERS", "MALES", "FEMALES", "FOR SALE", "FLOOR PRICE", "FEE"];
+
+	fn cells(&self) -> Vec<String> {
+		vec![
+			self.kitties.to_string(),
+			self.owners.to_string(),
+			self.males.to_string(),
+			self.females.to_string(),
+			self.for_sale.to_string(),
+			self.floor_price.map_or_else(|| "-".into(), |price| price.to_string()),
+			format!("{:?}", self.marketplace_fee),
+		]
+	}
+}
+
+impl KittiesCmd {
+	pub fn params(&self) -> &InspectParams {
+		match self {
+			KittiesCmd::List { params, .. } |
+			KittiesCmd::Show { params, .. } |
+			KittiesCmd::OwnedBy { params, .. } |
+			KittiesCmd::Stats { params } => params,
+		}
+	}
+
+	pub fn run(&self, client: &FullClient) -> sc_cli::Result<()> {
+		let params = self.params();
+		let state = State::at(client, params.at.as_ref())?;
+
+		match self {
+			KittiesCmd::List { for_sale, .. } => {
+				let kitties = state.kitties()?.into_iter();
+				let kitties = kitties.filter(|kitty| !for_sale || kitty.price.is_some());
+				print(params.output, kitties.map(KittyRow::from).collect())
+			},
+			KittiesCmd::Show { dna, .. } => {
+				let kitty = state.kitty(dna)?.ok_or_else(|| {
+					sc_cli::Error::Input(format!("No kitty with DNA 0x{}", HexDisplay::from(dna)))
+				})?;
+				print(params.output, vec![KittyRow::from(kitty)])
+			},
+			KittiesCmd::OwnedBy { account, .. } => {
+				let mut kitties = Vec::new();
+				for dna in state.kitties_owned(account)? {
+					kitties.extend(state.kitty(&dna)?.map(KittyRow::from));
+				}
+				print(params.output, kitties)
+			},
+			KittiesCmd::Stats { .. } => {
+				let kitties = state.kitties()?;
+				let gender = |gender| kitties.iter().filter(|kitty| kitty.gender == gender).count();
+				let prices = kitties.iter().filter_map(|kitty| kitty.price);
+				let stats = KittyStats {
+					kitties: state.value(keys::kitty_count())?.unwrap_or_default(),
+					owners: kitties.iter().map(|kitty| &kitty.owner).collect::<BTreeSet<_>>().len(),
+					males: gender(Gender::Male),
+					females: gender(Gender::Female),
+					for_sale: prices.clone().count(),
+					floor_price: prices.min(),
+					marketplace_fee: state.value(keys::marketplace_fee())?.unwrap_or_default(),
+				};
+				print(params.output, vec![stats])
+			},
+		}
+	}
+}
+
+impl PoeCmd {
+	pub fn params(&self) -> &InspectParams {
+		match self {
+			PoeCmd::Show { params, .. } | PoeCmd::List { params, .. } => params,
+		}
+	}
+
+	pub fn run(&self, client: &FullClient) -> sc_cli::Result<()> {
+		let params = self.params();
+		let state = State::at(client, params.at.as_ref())?;
+
+		match self {
+			PoeCmd::Show { claim, .. } => {
+				let (owner, block) = state.claim(claim)?.ok_or_else(|| {
+					sc_cli::Error::Input(format!("No claim 0x{}", HexDisplay::from(claim)))
+				})?;
+				print(params.output, vec![ClaimRow { claim: claim.clone().into(), owner, block }])
+			},
+			PoeCmd::List { owner, .. } => {
+				let claims = state.claims()?.into_iter();
+				let claims =
+					claims.filter(|claim| owner.as_ref().map_or(true, |o| &claim.owner == o));
+				print(params.output, claims.collect())
+			},
+		}
+	}
+}
+
+/// The state of a block.
+struct State<'a> {
+	client: &'a FullClient,
+	at: BlockId<Block>,
+}
+
+impl<'a> State<'a> {
+	fn at(client: &'a FullClient, at: Option<&BlockNumberOrHash>) -> sc_cli::Result<Self> {
+		let at = match at {
+			Some(at) => at.parse::<Block>()?,
+			None => BlockId::Hash(client.info().best_hash),
+		};
+		Ok(State { client, at })
+	}
+
+	fn value<T: Decode>(&self, key: Vec<u8>) -> sc_cli::Result<Option<T>> {
+		self.client
+			.storage(&self.at, &StorageKey(key))?
+			.map(|data| decode(&data.0))
+			.transpose()
+	}
+
+	/// The keys, without `prefix`, and values of a map.
+	fn pairs<T: Decode>(&self, prefix: Vec<u8>) -> sc_cli::Result<Vec<(Vec<u8>, T)>> {
+		self.client
+			.storage_pairs(&self.at, &StorageKey(prefix.clone()))?
+			.into_iter()
+			.map(|(key, data)| Ok((key.0[prefix.len()..].to_vec(), decode(&data.0)?)))
+			.collect()
+	}
+
+	fn kitties(&self) -> sc_cli::Result<Vec<Kitty<Runtime>>> {
+		let kitties = self.pairs(keys::kitties())?;
+		Ok(kitties.into_iter().map(|(_, kitty)| kitty).collect())
+	}
+
+	fn kitty(&self, dna: &[u8; 16]) -> sc_cli::Result<Option<Kitty<Runtime>>> {
+		self.value(keys::kitty(dna))
+	}
+
+	fn kitties_owned(&self, owner: &AccountId) -> sc_cli::Result<Vec<[u8; 16]>> {
+		Ok(self.value(keys::kitties_owned(owner))?.unwrap_or_default())
+	}
+
+	fn claim(&self, claim: &[u8]) -> sc_cli::Result<Option<(AccountId, BlockNumber)>> {
+		self.value(keys::proof(claim))
+	}
+
+	fn claims(&self) -> sc_cli::Result<Vec<ClaimRow>> {
+		self.pairs::<(AccountId, BlockNumber)>(keys::proofs())?
+			.into_iter()
+			.map(|(key, (owner, block))| {
+				// The key is the hash of the claim followed by the claim.
+				let claim: Vec<u8> = decode(&key[16..])?;
+				Ok(ClaimRow { claim: claim.into(), owner, block })
+			})
+			.collect()
+	}
+}
+
+fn decode<T: Decode>(mut data: &[u8]) -> sc_cli::Result<T> {
+	T::decode(&mut data).map_err(|e| sc_cli::Error::Input(format!("Undecodable state: {}", e)))
+}
+
+/// Something printed as a line of a table.
+trait Row: Serialize {
+	const HEADER: &'static [&'static str];
+
+	fn cells(&self) -> Vec<String>;
+}
+
+fn print<R: Row>(format: Format, rows: Vec<R>) -> sc_cli::Result<()> {
+	match format {
+		Format::Json => {
+			let json = serde_json::to_string_pretty(&rows).map_err(|e| e.to_string())?;
+			println!("{}", json);
+		},
+		Format::Table => {
+			let header = R::HEADER.iter().map(|title| title.to_string()).collect();
+			let lines: Vec<Vec<String>> =
+				Some(header).into_iter().chain(rows.iter().map(Row::cells)).collect();
+			let widths: Vec<usize> = (0..R::HEADER.len())
+				.map(|column| lines.iter().map(|line| line[column].len()).max().unwrap_or(0))
+				.collect();
+			for line in lines {
+				let cells: Vec<String> = line
+					.iter()
+					.zip(&widths)
+					.map(|(cell, width)| format!("{:<width$}", cell, width = width))
+					.collect();
+				println!("{}", cells.join("  ").trim_end());
+			}
+		},
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn dna_is_16_bytes_of_hex() {
+		assert_eq!(parse_dna("0x000102030405060708090a0b0c0d0e0f").unwrap()[15], 15);
+		assert_eq!(parse_dna("0f0e0d0c0b0a09080706050403020100").unwrap()[0], 15);
+		assert!(parse_dna("0x0001").is_err());
+		assert!(parse_dna("kitty").is_err());
+	}
+}
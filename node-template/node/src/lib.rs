@@ -1,3 +1,14 @@
 pub mod chain_spec;
+pub mod cli;
+#[cfg(feature = "evm")]
+pub mod eth;
+pub mod events;
+pub mod generate_spec;
+pub mod indexer;
+pub mod inspect;
+pub mod load_test;
+pub mod metrics;
+pub mod remote_keystore;
 pub mod rpc;
 pub mod service;
+pub mod snapshot;
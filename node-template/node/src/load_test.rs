@@ -0,0 +1,434 @@
+//! The `load-test` subcommand: a running dev node flooded with kitty and PoE transactions from
+//! many accounts, to see how many it includes per second and how long they wait.
+//!
+//! The accounts are derived from the dev phrase as `//load//<n>` and endowed by Alice. Before the
+//! test, every account creates the kitties it will transfer and those its buyer will buy, and puts
+//! the latter on sale: account `n` buys from account `n + 1`. Then every account sends its
+//! transactions back to back, in an order set by the mix, without waiting for their inclusion.
+
+use futures::{future, FutureExt, StreamExt};
+use node_template_client::{calls, Client, TransactionStatus};
+use node_template_runtime::{
+	AccountId, BalancesCall, Call, Event, Hash, Runtime, UncheckedExtrinsic, UNIT,
+};
+use sp_core::{crypto::DEV_PHRASE, sr25519, Pair};
+use sp_runtime::{DispatchError, ModuleError};
+use std::{
+	collections::BTreeMap,
+	fmt,
+	str::FromStr,
+	time::{Duration, Instant},
+};
+
+/// The calls of the test.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Op {
+	CreateKitty,
+	Transfer,
+	BuyKitty,
+	CreateClaim,
+}
+
+impl Op {
+	const ALL: [Op; 4] = [Op::CreateKitty, Op::Transfer, Op::BuyKitty, Op::CreateClaim];
+
+	fn name(&self) -> &'static str {
+		match self {
+			Op::CreateKitty => "create_kitty",
+			Op::Transfer => "transfer",
+			Op::BuyKitty => "buy_kitty",
+			Op::CreateClaim => "create_claim",
+		}
+	}
+}
+
+/// The share of each call among the transactions, as `call=weight` pairs separated by commas.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mix(Vec<(Op, u32)>);
+
+impl FromStr for Mix {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut mix = Vec::new();
+		for pair in s.split(',') {
+			let (name, weight) = pair
+				.split_once('=')
+				.ok_or_else(|| format!("invalid `{}`, expected `call=weight`", pair))?;
+			let op = Op::ALL
+				.into_iter()
+				.find(|op| op.name() == name.trim())
+				.ok_or_else(|| format!("unknown call `{}`", name))?;
+			let weight = weight.trim().parse().map_err(|e| format!("invalid weight: {}", e))?;
+			mix.push((op, weight));
+		}
+		if mix.iter().all(|(_, weight)| *weight == 0) {
+			return Err("the mix has no call".into())
+		}
+		Ok(Mix(mix))
+	}
+}
+
+impl Mix {
+	/// The calls of an account, spread along the weights of the mix. Accounts start at different
+	/// offsets of the same cycle, so the calls of a block are mixed too.
+	fn schedule(&self, account: u32, transactions: u32) -> Vec<Op> {
+		let cycle: Vec<Op> = self
+			.0
+			.iter()
+			.flat_map(|(op, weight)| std::iter::repeat(*op).take(*weight as usize))
+			.collect();
+		(0..transactions as usize)
+			.map(|n| cycle[(n + account as usize) % cycle.len()])
+			.collect()
+	}
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct LoadTestCmd {
+	/// The WebSocket RPC endpoint of the node.
+	#[clap(long, default_value = "ws://127.0.0.1:9944")]
+	pub url: String,
+
+	/// The number of accounts sending transactions.
+	#[clap(long, default_value = "16")]
+	pub accounts: u32,
+
+	/// The number of transactions of each account.
+	#[clap(long, default_value = "20")]
+	pub transactions: u32,
+
+	/// The calls of the test with their weight, among `create_kitty`, `transfer`, `buy_kitty` and
+	/// `create_claim`.
+	#[clap(long, default_value = "create_kitty=4,transfer=2,buy_kitty=1,create_claim=3")]
+	pub mix: Mix,
+
+	/// What Alice gives to each account before the test, in UNIT.
+	#[clap(long, default_value = "1000")]
+	pub endowment: u128,
+
+	/// The price of the kitties put on sale, in UNIT.
+	#[clap(long, default_value = "1")]
+	pub price: u128,
+
+	/// How long a transaction may wait for its inclusion, in seconds.
+	#[clap(long, default_value = "120")]
+	pub timeout: u64,
+}
+
+/// What became of a transaction.
+enum Outcome {
+	Included { latency: Duration },
+	Failed { reason: String },
+}
+
+/// The outcomes of the transactions of the test.
+#[derive(Default)]
+struct Report {
+	elapsed: Duration,
+	sent: BTreeMap<Op, usize>,
+	latencies: Vec<Duration>,
+	failures: BTreeMap<String, usize>,
+}
+
+impl fmt::Display for Report {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let sent: usize = self.sent.values().sum();
+		let included = self.latencies.len();
+		let seconds = self.elapsed.as_secs_f64();
+		writeln!(f, "Transactions sent: {}", sent)?;
+		for (op, count) in &self.sent {
+			writeln!(f, "  {:<14}{}", op.name(), count)?;
+		}
+		writeln!(f, "Included successfully: {} in {:.1}s", included, seconds)?;
+		writeln!(f, "Throughput: {:.1} TPS", included as f64 / seconds.max(f64::EPSILON))?;
+
+		let mut latencies = self.latencies.clone();
+		latencies.sort();
+		if let (Some(min), Some(max)) = (latencies.first(), latencies.last()) {
+			let percentile = |p: usize| latencies[(latencies.len() - 1) * p / 100];
+			let mean = latencies.iter().sum::<Duration>() / latencies.len() as u32;
+			writeln!(
+				f,
+				"Inclusion latency: min {:?}, mean {:?}, p50 {:?}, p95 {:?}, max {:?}",
+				min,
+				mean,
+				percentile(50),
+				percentile(95),
+				max
+			)?;
+		}
+
+		writeln!(f, "Failed: {}", self.failures.values().sum::<usize>())?;
+		for (reason, count) in &self.failures {
+			writeln!(f, "  {:>6}  {}", count, reason)?;
+		}
+		Ok(())
+	}
+}
+
+/// An account of the test, with the kitties it prepared.
+struct Account {
+	pair: sr25519::Pair,
+	id: AccountId,
+	schedule: Vec<Op>,
+	/// Kitties to give away.
+	transferable: Vec<[u8; 16]>,
+	/// Kitties on sale to the previous account.
+	on_sale: Vec<[u8; 16]>,
+}
+
+impl Account {
+	fn count(&self, op: Op) -> usize {
+		self.schedule.iter().filter(|scheduled| **scheduled == op).count()
+	}
+}
+
+impl LoadTestCmd {
+	pub fn run(&self) -> sc_cli::Result<()> {
+		if self.accounts < 2 {
+			return Err(sc_cli::Error::Input("The test needs two accounts at least".into()))
+		}
+		let runtime = sc_cli::build_runtime()?;
+		let report = runtime.block_on(self.load_test()).map_err(sc_cli::Error::Application)?;
+		print!("{}", report);
+		Ok(())
+	}
+
+	async fn load_test(&self) -> Result<Report, Box<dyn std::error::Error + Send + Sync>> {
+		let client = Client::connect(&self.url).await?;
+		let mut accounts: Vec<Account> = (0..self.accounts)
+			.map(|n| {
+				let seed = format!("{}//load//{}", DEV_PHRASE, n);
+				let pair =
+					sr25519::Pair::from_string(&seed, None).expect("Dev seeds are valid; qed");
+				Account {
+					id: pair.public().into(),
+					pair,
+					schedule: self.mix.schedule(n, self.transactions),
+					transferable: Vec::new(),
+					on_sale: Vec::new(),
+				}
+			})
+			.collect();
+
+		println!("Endowing {} accounts", accounts.len());
+		let alice = sr25519::Pair::from_string("//Alice", None).expect("Dev seeds are valid; qed");
+		let endowments = accounts.iter().map(|account| {
+			let transfer = BalancesCall::transfer {
+				dest: account.id.clone().into(),
+				value: self.endowment * UNIT,
+			};
+			(&alice, Call::from(transfer))
+		});
+		self.prepare(&client, endowments.collect()).await?;
+
+		println!("Creating the kitties to transfer and to buy");
+		let buys: Vec<usize> = accounts.iter().map(|account| account.count(Op::BuyKitty)).collect();
+		let creations = accounts.iter().enumerate().flat_map(|(n, account)| {
+			let buyer = (n + accounts.len() - 1) % accounts.len();
+			let count = account.count(Op::Transfer) + buys[buyer];
+			std::iter::repeat((&account.pair, calls::kitties::create_kitty())).take(count)
+		});
+		self.prepare(&client, creations.collect()).await?;
+
+		for (n, account) in accounts.iter_mut().enumerate() {
+			let mut owned = client.kitties_owned(&account.id).await?;
+			let buyer = (n + self.accounts as usize - 1) % self.accounts as usize;
+			account.on_sale = owned.split_off(owned.len().saturating_sub(buys[buyer]));
+			account.transferable = owned;
+		}
+		let listings = accounts.iter().flat_map(|account| {
+			let price = Some(self.price * UNIT);
+			let listings = account.on_sale.iter();
+			listings.map(move |kitty| (&account.pair, calls::kitties::set_price(*kitty, price)))
+		});
+		self.prepare(&client, listings.collect()).await?;
+
+		println!(
+			"Sending {} transactions from {} accounts",
+			self.transactions * self.accounts,
+			self.accounts
+		);
+		let mut report = Report::default();
+		let run = Instant::now();
+		let mut pending = Vec::new();
+		for n in 0..accounts.len() {
+			let seller = &accounts[(n + 1) % accounts.len()];
+			let account = &accounts[n];
+			let recipient = seller.id.clone();
+			let (mut transferable, mut for_sale) =
+				(account.transferable.iter(), seller.on_sale.iter());
+
+			for (index, op) in account.schedule.iter().enumerate() {
+				let call = match op {
+					Op::CreateKitty => Some(calls::kitties::create_kitty()),
+					Op::Transfer => transferable
+						.next()
+						.map(|kitty| calls::kitties::transfer(recipient.clone(), *kitty)),
+					Op::BuyKitty => for_sale
+						.next()
+						.map(|kitty| calls::kitties::buy_kitty(*kitty, self.price * UNIT)),
+					Op::CreateClaim => Some(calls::poe::create_claim(claim(n, index))),
+				};
+				*report.sent.entry(*op).or_default() += 1;
+				match call {
+					Some(call) => pending.push(self.send(&client, &account.pair, call)),
+					None => pending.push(
+						future::ready(Outcome::Failed {
+							reason: format!("{}: no kitty prepared", op.name()),
+						})
+						.boxed_local(),
+					),
+				}
+			}
+		}
+
+		for outcome in future::join_all(pending).await {
+			match outcome {
+				Outcome::Included { latency } => report.latencies.push(latency),
+				Outcome::Failed { reason } => *report.failures.entry(reason).or_default() += 1,
+			}
+		}
+		report.elapsed = run.elapsed();
+		Ok(report)
+	}
+
+	/// Send the transactions of a step before the test and wait for all of them.
+	async fn prepare(
+		&self,
+		client: &Client,
+		transactions: Vec<(&sr25519::Pair, Call)>,
+	) -> Result<(), String> {
+		let sent = transactions.into_iter().map(|(signer, call)| self.send(client, signer, call));
+		let failures: Vec<String> = future::join_all(sent)
+			.await
+			.into_iter()
+			.filter_map(|outcome| match outcome {
+				Outcome::Included { .. } => None,
+				Outcome::Failed { reason } => Some(reason),
+			})
+			.collect();
+		match failures.first() {
+			None => Ok(()),
+			Some(reason) => Err(format!("{} transactions failed: {}", failures.len(), reason)),
+		}
+	}
+
+	/// Submit a transaction, wait for its inclusion and check that it succeeded.
+	fn send<'a>(
+		&'a self,
+		client: &'a Client,
+		signer: &'a sr25519::Pair,
+		call: Call,
+	) -> future::LocalBoxFuture<'a, Outcome> {
+		async move {
+			let submitted = Instant::now();
+			let failed = |reason: String| Outcome::Failed { reason };
+			let xt = match client.sign(signer, call).await {
+				Ok(xt) => xt,
+				Err(e) => return failed(e.to_string()),
+			};
+			let mut statuses = match client.watch(signer, &xt).await {
+				Ok(statuses) => statuses,
+				Err(e) => return failed(e.to_string()),
+			};
+
+			let timeout = futures_timer::Delay::new(Duration::from_secs(self.timeout)).fuse();
+			futures::pin_mut!(timeout);
+			let block = loop {
+				let status = futures::select! {
+					status = statuses.next().fuse() => status,
+					_ = timeout => return failed("timed out".into()),
+				};
+				match status {
+					Some(Ok(TransactionStatus::InBlock(block))) => break block,
+					Some(Ok(TransactionStatus::Future | TransactionStatus::Ready)) |
+					Some(Ok(TransactionStatus::Broadcast(_))) => continue,
+					Some(Ok(status)) => return failed(format!("{:?}", status)),
+					Some(Err(e)) => return failed(e.to_string()),
+					None => return failed("watch ended before inclusion".into()),
+				}
+			};
+			let latency = submitted.elapsed();
+
+			match dispatch_error(client, block, &xt).await {
+				Ok(None) => Outcome::Included { latency },
+				Ok(Some(error)) => failed(error),
+				Err(e) => failed(format!("events unavailable: {}", e)),
+			}
+		}
+		.boxed_local()
+	}
+}
+
+/// A claim unique to the test, the account and the transaction, of 16 bytes.
+fn claim(account: usize, transaction: usize) -> Vec<u8> {
+	let run = std::time::SystemTime::now()
+		.duration_since(std::time::UNIX_EPOCH)
+		.map(|since| since.as_secs())
+		.unwrap_or_default();
+	[run.to_be_bytes(), ((account as u64) << 32 | transaction as u64).to_be_bytes()].concat()
+}
+
+/// Why the extrinsic `xt` of `block` failed, if it did.
+async fn dispatch_error(
+	client: &Client,
+	block: Hash,
+	xt: &UncheckedExtrinsic,
+) -> Result<Option<String>, node_template_client::Error> {
+	let events = client.extrinsic_events(block, xt).await?;
+	Ok(events.into_iter().find_map(|event| match event {
+		Event::System(frame_system::Event::ExtrinsicFailed { dispatch_error, .. }) =>
+			Some(describe(dispatch_error)),
+		_ => None,
+	}))
+}
+
+/// The name of the error of an app pallet, or the error as it is.
+fn describe(error: DispatchError) -> String {
+	use codec::Decode;
+	use frame_support::traits::PalletInfoAccess;
+
+	if let DispatchError::Module(ModuleError { index, error: bytes, .. }) = error {
+		let index = index as usize;
+		if index == pallet_kitties::Pallet::<Runtime>::index() {
+			if let Ok(error) = pallet_kitties::Error::<Runtime>::decode(&mut &bytes[..]) {
+				return format!("SubstrateKitties::{:?}", error)
+			}
+		}
+		if index == pallet_poe::Pallet::<Runtime>::index() {
+			if let Ok(error) = pallet_poe::Error::<Runtime>::decode(&mut &bytes[..]) {
+				return format!("PoeModule::{:?}", error)
+			}
+		}
+	}
+	format!("{:?}", error)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_support::traits::Get;
+
+	#[test]
+	fn mix_is_parsed_and_scheduled() {
+		let mix: Mix = "create_kitty=2, buy_kitty=1,create_claim=0".parse().unwrap();
+		assert_eq!(
+			mix.schedule(0, 4),
+			vec![Op::CreateKitty, Op::CreateKitty, Op::BuyKitty, Op::CreateKitty]
+		);
+		assert_eq!(mix.schedule(1, 3), vec![Op::CreateKitty, Op::BuyKitty, Op::CreateKitty]);
+
+		assert!("create_kitty=0".parse::<Mix>().is_err());
+		assert!("breed_kitty=1".parse::<Mix>().is_err());
+		assert!("transfer".parse::<Mix>().is_err());
+	}
+
+	#[test]
+	fn claims_fit_the_claim_limit() {
+		let limit = <Runtime as pallet_poe::Config>::ClaimLimitSize::get() as usize;
+		assert_eq!(claim(3, 7).len(), limit);
+		assert_ne!(claim(3, 7), claim(7, 3));
+	}
+}
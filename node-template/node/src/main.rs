@@ -7,7 +7,17 @@ mod service;
 mod cli;
 mod command;
 mod command_helper;
+#[cfg(feature = "evm")]
+mod eth;
+mod events;
+mod generate_spec;
+mod indexer;
+mod inspect;
+mod load_test;
+mod metrics;
+mod remote_keystore;
 mod rpc;
+mod snapshot;
 
 fn main() -> sc_cli::Result<()> {
 	command::run()
@@ -0,0 +1,267 @@
+//! Prometheus metrics of the kitties marketplace and PoE, next to Substrate's own when the
+//! Prometheus endpoint is enabled.
+//!
+//! Counters follow the events of finalized blocks, so they never count a block that is reverted.
+//! They start from zero when the node starts, like every Prometheus counter. Gauges are read from
+//! the state of the last finalized block when the node starts, then follow the events too.
+
+use crate::{
+	events::{block_events, EventRecord},
+	service::FullClient,
+};
+use codec::Decode;
+use futures::StreamExt;
+use node_template_client::keys;
+use node_template_runtime::{Event, Hash, Runtime, UNIT};
+use prometheus_endpoint::{register, Counter, Gauge, PrometheusError, Registry, F64, U64};
+use sc_client_api::{BlockchainEvents, StorageProvider};
+use sp_blockchain::HeaderBackend;
+use sp_core::storage::StorageKey;
+use sp_runtime::generic::BlockId;
+use std::{collections::HashSet, sync::Arc};
+
+/// The metrics of the app layer.
+pub struct Metrics {
+	kitties: Gauge<U64>,
+	kitties_for_sale: Gauge<U64>,
+	/// The DNA of the kitties with a price, counted by `kitties_for_sale`.
+	for_sale: HashSet<[u8; 16]>,
+	kitties_created: Counter<U64>,
+	kitty_transfers: Counter<U64>,
+	kitty_sales: Counter<U64>,
+	sale_volume: Counter<F64>,
+	claims_created: Counter<U64>,
+	claim_batches_created: Counter<U64>,
+	claims_revoked: Counter<U64>,
+	claim_transfers: Counter<U64>,
+}
+
+impl Metrics {
+	/// Create the metrics and register them to `registry`.
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Metrics {
+			kitties: register(Gauge::new("kitties", "Number of kitties")?, registry)?,
+			kitties_for_sale: register(
+				Gauge::new("kitties_for_sale", "Number of kitties with a price")?,
+				registry,
+			)?,
+			for_sale: HashSet::new(),
+			kitties_created: register(
+				Counter::new("kitties_created_total", "Kitties created or bred")?,
+				registry,
+			)?,
+			kitty_transfers: register(
+				Counter::new("kitty_transfers_total", "Kitties given away, sales excluded")?,
+				registry,
+			)?,
+			kitty_sales: register(Counter::new("kitty_sales_total", "Kitties sold")?, registry)?,
+			sale_volume: register(
+				Counter::new("kitty_sale_volume_total", "Sum of the kitty sale prices, in UNIT")?,
+				registry,
+			)?,
+			claims_created: register(
+				Counter::new("poe_claims_created_total", "Claims created")?,
+				registry,
+			)?,
+			claim_batches_created: register(
+				Counter::new("poe_claim_batches_created_total", "Claim batches created")?,
+				registry,
+			)?,
+			claims_revoked: register(
+				Counter::new(
+					"poe_claims_revoked_total",
+					"Claims revoked by their owner or by the authority",
+				)?,
+				registry,
+			)?,
+			claim_transfers: register(
+				Counter::new("poe_claim_transfers_total", "Claims transferred")?,
+				registry,
+			)?,
+		})
+	}
+
+	/// Count the app events of a block.
+	pub fn observe_events(&mut self, events: &[EventRecord]) {
+		// The last sale, as `buy_kitty` deposits `Transferred` right after `Sold`.
+		let mut sold = None;
+		for record in events {
+			match &record.event {
+				Event::SubstrateKitties(event) => match event {
+					pallet_kitties::Event::Created { .. } => {
+						self.kitties_created.inc();
+						self.kitties.inc();
+					},
+					pallet_kitties::Event::PriceSet { kitty, price: Some(_) } => {
+						self.for_sale.insert(*kitty);
+					},
+					pallet_kitties::Event::PriceSet { kitty, price: None } => {
+						self.for_sale.remove(kitty);
+					},
+					pallet_kitties::Event::Transferred { kitty, .. } => {
+						// Transfers take kitties off sale.
+						self.for_sale.remove(kitty);
+						if sold != Some((&record.phase, kitty)) {
+							self.kitty_transfers.inc();
+						}
+					},
+					pallet_kitties::Event::Sold { kitty, price, .. } => {
+						sold = Some((&record.phase, kitty));
+						self.kitty_sales.inc();
+						self.sale_volume.inc_by(*price as f64 / UNIT as f64);
+					},
+					_ => {},
+				},
+				Event::PoeModule(event) => match event {
+					pallet_poe::Event::ClaimCreated(..) => self.claims_created.inc(),
+					pallet_poe::Event::BatchClaimCreated(..) => self.claim_batches_created.inc(),
+					pallet_poe::Event::ClaimRevoked(..) |
+					pallet_poe::Event::ClaimRevokedByAuthority(..) => self.claims_revoked.inc(),
+					pallet_poe::Event::ClaimTransferred(..) => self.claim_transfers.inc(),
+					_ => {},
+				},
+				_ => {},
+			}
+		}
+		self.kitties_for_sale.set(self.for_sale.len() as u64);
+	}
+
+	/// Start the kitty gauges from the state of the block `hash`. Kitties for sale are found by
+	/// going through all of them, once: events keep the gauges up to date from there.
+	pub fn observe_state(&mut self, client: &FullClient, hash: Hash) -> sp_blockchain::Result<()> {
+		let at = BlockId::Hash(hash);
+
+		let count = client.storage(&at, &StorageKey(keys::kitty_count()))?;
+		let count = count.and_then(|data| u64::decode(&mut &data.0[..]).ok()).unwrap_or_default();
+		self.kitties.set(count);
+
+		self.for_sale = client
+			.storage_pairs(&at, &StorageKey(keys::kitties()))?
+			.into_iter()
+			.filter_map(|(_, data)| pallet_kitties::Kitty::<Runtime>::decode(&mut &data.0[..]).ok())
+			.filter(|kitty| kitty.price.is_some())
+			.map(|kitty| kitty.dna)
+			.collect();
+		self.kitties_for_sale.set(self.for_sale.len() as u64);
+
+		Ok(())
+	}
+}
+
+/// Update the metrics with every block finalized until the node stops. Reading blocks blocks the
+/// thread, so this is spawned as a blocking task.
+pub async fn run(mut metrics: Metrics, client: Arc<FullClient>) {
+	let mut finality_notifications = client.finality_notification_stream();
+	let start = client.info();
+	if let Err(e) = metrics.observe_state(&client, start.finalized_hash) {
+		log::warn!(target: "metrics", "Kitty gauges start from zero: {}", e);
+	}
+
+	while let Some(notification) = finality_notifications.next().await {
+		let finalized = notification.tree_route.iter().chain(Some(&notification.hash));
+		let result = finalized
+			.map(|hash| {
+				// The gauges already include the blocks finalized before they were read.
+				match client.number(*hash)? {
+					Some(number) if number > start.finalized_number => {},
+					_ => return Ok(()),
+				}
+				block_events(&*client, *hash).map(|events| metrics.observe_events(&events))
+			})
+			.collect::<sp_blockchain::Result<()>>();
+		if let Err(e) = result {
+			log::warn!(target: "metrics", "App metrics not updated: {}", e);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_system::Phase;
+	use sp_keyring::Sr25519Keyring::{Alice, Bob};
+
+	fn record(event: impl Into<Event>) -> EventRecord {
+		EventRecord { phase: Phase::ApplyExtrinsic(0), event: event.into(), topics: vec![] }
+	}
+
+	#[test]
+	fn events_are_counted() {
+		let mut metrics = Metrics::register(&Registry::new()).unwrap();
+		let (alice, bob) = (Alice.to_account_id(), Bob.to_account_id());
+		let claim: pallet_poe::BoundedClaim<Runtime> = b"deed".to_vec().try_into().unwrap();
+
+		metrics.observe_events(&[
+			record(pallet_kitties::Event::<Runtime>::Created {
+				kitty: [1; 16],
+				owner: alice.clone(),
+			}),
+			record(pallet_kitties::Event::<Runtime>::Sold {
+				seller: alice.clone(),
+				buyer: bob.clone(),
+				kitty: [1; 16],
+				price: 3 * UNIT / 2,
+			}),
+			record(pallet_poe::Event::<Runtime>::ClaimCreated(bob.clone(), claim.clone())),
+			record(pallet_poe::Event::<Runtime>::ClaimRevoked(bob, claim)),
+		]);
+		metrics.observe_events(&[record(pallet_kitties::Event::<Runtime>::Sold {
+			seller: Bob.to_account_id(),
+			buyer: alice,
+			kitty: [1; 16],
+			price: UNIT,
+		})]);
+
+		assert_eq!(metrics.kitties_created.get(), 1);
+		assert_eq!(metrics.kitty_sales.get(), 2);
+		assert_eq!(metrics.sale_volume.get(), 2.5);
+		assert_eq!(metrics.claims_created.get(), 1);
+		assert_eq!(metrics.claims_revoked.get(), 1);
+		assert_eq!(metrics.claim_transfers.get(), 0);
+	}
+
+	#[test]
+	fn sales_are_not_counted_as_transfers() {
+		let mut metrics = Metrics::register(&Registry::new()).unwrap();
+		let (alice, bob) = (Alice.to_account_id(), Bob.to_account_id());
+		let in_extrinsic = |index, event: pallet_kitties::Event<Runtime>| EventRecord {
+			phase: Phase::ApplyExtrinsic(index),
+			event: event.into(),
+			topics: vec![],
+		};
+
+		metrics.observe_events(&[
+			in_extrinsic(0, pallet_kitties::Event::PriceSet { kitty: [1; 16], price: Some(UNIT) }),
+			in_extrinsic(0, pallet_kitties::Event::PriceSet { kitty: [2; 16], price: Some(UNIT) }),
+		]);
+		assert_eq!(metrics.kitties_for_sale.get(), 2);
+
+		metrics.observe_events(&[
+			in_extrinsic(
+				0,
+				pallet_kitties::Event::Sold {
+					seller: alice.clone(),
+					buyer: bob.clone(),
+					kitty: [1; 16],
+					price: UNIT,
+				},
+			),
+			in_extrinsic(
+				0,
+				pallet_kitties::Event::Transferred {
+					from: alice.clone(),
+					to: bob.clone(),
+					kitty: [1; 16],
+				},
+			),
+			in_extrinsic(
+				1,
+				pallet_kitties::Event::Transferred { from: bob, to: alice, kitty: [1; 16] },
+			),
+		]);
+
+		assert_eq!(metrics.kitty_sales.get(), 1);
+		assert_eq!(metrics.kitty_transfers.get(), 1);
+		assert_eq!(metrics.kitties_for_sale.get(), 1);
+	}
+}
@@ -0,0 +1,641 @@
+//! A keystore whose keys live in a signing daemon, so the session keys of a validator can be kept
+//! on a separate hardened host. Selected with `--keystore-uri`.
+//!
+//! The daemon listens on `unix://<path>` or `tcp://<host>:<port>`. Every request is a connection
+//! carrying one line of JSON, answered by one line of JSON (wrapped here):
+//!
+//! ```text
+//! -> {"method":"sign","params":{
+//!      "key_type":"aura","crypto":"sr25","public":"0x..","message":"0x.."}}
+//! <- {"result":"0x.."}
+//! ```
+//!
+//! Failures are answered with `{"error":"<reason>"}`. Key types and crypto types are their four
+//! character ids, bytes are `0x` prefixed hex. See [`Request`] for the methods.
+//!
+//! The protocol has no authentication nor encryption: expose a TCP daemon only over a tunnel or a
+//! private network.
+//!
+//! Requests are sent over blocking sockets. The async [`CryptoStore`] API, used by the Aura and
+//! GRANDPA tasks, sends them from the blocking thread pool of tokio so that a slow daemon doesn't
+//! stall the executor.
+
+use serde::{Deserialize, Serialize};
+use sp_core::{
+	crypto::{CryptoTypeId, CryptoTypePublicPair, KeyTypeId},
+	ecdsa, ed25519, sr25519, Bytes,
+};
+use sp_keystore::{
+	vrf::{VRFSignature, VRFTranscriptData},
+	CryptoStore, Error, SyncCryptoStore,
+};
+use std::{
+	io::{self, BufRead, BufReader, Read, Write},
+	net::{TcpStream, ToSocketAddrs},
+	os::unix::net::UnixStream,
+	path::PathBuf,
+	sync::Arc,
+	time::Duration,
+};
+
+/// How long the daemon has to accept a connection, and to answer a request.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A request to the signing daemon.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum Request {
+	/// The public keys of a key and crypto type, answered with a list of bytes.
+	PublicKeys { key_type: String, crypto: String },
+	/// Generate a key pair, from `seed` if given, answered with its public key.
+	GenerateNew { key_type: String, crypto: String, seed: Option<String> },
+	/// Store the key pair of `suri`, answered with `null`.
+	Insert { key_type: String, suri: String, public: Bytes },
+	/// The public keys of a key type, answered with a list of [`Key`].
+	Keys { key_type: String },
+	/// Whether all the keys are known, answered with a boolean.
+	HasKeys { keys: Vec<(String, Bytes)> },
+	/// Sign `message`, answered with the signature or `null` for an unknown key.
+	Sign { key_type: String, crypto: String, public: Bytes, message: Bytes },
+	/// Sign the 32 bytes hash `message` with an ECDSA key, answered like [`Request::Sign`].
+	EcdsaSignPrehashed { key_type: String, public: Bytes, message: Bytes },
+}
+
+/// The answer of the signing daemon.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Response {
+	/// `{"result": <value>}`
+	Result(serde_json::Value),
+	/// `{"error": "<reason>"}`
+	Error(String),
+}
+
+/// A public key and its crypto type.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Key {
+	/// The crypto type id, `sr25`, `ed25` or `ecds`.
+	pub crypto: String,
+	/// The public key.
+	pub public: Bytes,
+}
+
+#[derive(Clone)]
+enum Endpoint {
+	Unix(PathBuf),
+	Tcp(String),
+}
+
+/// A [`SyncCryptoStore`] forwarding every operation to a signing daemon.
+#[derive(Clone)]
+pub struct RemoteKeystore {
+	endpoint: Endpoint,
+}
+
+impl RemoteKeystore {
+	/// A keystore using the daemon at `uri`. Nothing is sent until a key is needed, the daemon
+	/// doesn't have to be up yet.
+	pub fn open(uri: &str) -> Result<Arc<Self>, String> {
+		let endpoint = if let Some(path) = uri.strip_prefix("unix://") {
+			Endpoint::Unix(path.into())
+		} else if let Some(address) = uri.strip_prefix("tcp://") {
+			Endpoint::Tcp(address.into())
+		} else {
+			return Err(format!("unsupported keystore URI {}, expected unix:// or tcp://", uri))
+		};
+		Ok(Arc::new(RemoteKeystore { endpoint }))
+	}
+
+	fn call<T: serde::de::DeserializeOwned>(&self, request: Request) -> Result<T, Error> {
+		let mut line = serde_json::to_vec(&request).map_err(|e| Error::Other(e.to_string()))?;
+		line.push(b'\n');
+
+		let answer = match &self.endpoint {
+			Endpoint::Unix(path) => UnixStream::connect(path).and_then(|stream| {
+				stream.set_read_timeout(Some(TIMEOUT))?;
+				stream.set_write_timeout(Some(TIMEOUT))?;
+				exchange(stream, &line)
+			}),
+			Endpoint::Tcp(address) => connect_tcp(address).and_then(|stream| {
+				stream.set_read_timeout(Some(TIMEOUT))?;
+				stream.set_write_timeout(Some(TIMEOUT))?;
+				exchange(stream, &line)
+			}),
+		}
+		.map_err(|e| {
+			log::warn!(target: "remote-keystore", "Signing daemon unreachable: {}", e);
+			Error::Unavailable
+		})?;
+
+		match serde_json::from_str(&answer) {
+			Ok(Response::Result(result)) =>
+				serde_json::from_value(result).map_err(|e| Error::Other(e.to_string())),
+			Ok(Response::Error(reason)) => Err(Error::Other(reason)),
+			Err(e) => Err(Error::Other(format!("invalid answer from the signing daemon: {}", e))),
+		}
+	}
+
+	/// Run `f` with a copy of the keystore on the blocking thread pool.
+	async fn blocking<R: Send + 'static>(
+		&self,
+		f: impl FnOnce(&RemoteKeystore) -> R + Send + 'static,
+	) -> R {
+		let keystore = self.clone();
+		tokio::task::spawn_blocking(move || f(&keystore))
+			.await
+			.unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+	}
+
+	fn public_keys<P>(&self, id: KeyTypeId, crypto: CryptoTypeId) -> Vec<P>
+	where
+		P: for<'a> TryFrom<&'a [u8]>,
+	{
+		let request = Request::PublicKeys { key_type: key_type(id), crypto: crypto_type(crypto) };
+		match self.call::<Vec<Bytes>>(request) {
+			Ok(keys) => keys.iter().filter_map(|key| P::try_from(&key[..]).ok()).collect(),
+			Err(e) => {
+				log::warn!(target: "remote-keystore", "Listing keys failed: {}", e);
+				Vec::new()
+			},
+		}
+	}
+
+	fn generate_new<P>(
+		&self,
+		id: KeyTypeId,
+		crypto: CryptoTypeId,
+		seed: Option<&str>,
+	) -> Result<P, Error>
+	where
+		P: for<'a> TryFrom<&'a [u8]>,
+	{
+		let public: Bytes = self.call(Request::GenerateNew {
+			key_type: key_type(id),
+			crypto: crypto_type(crypto),
+			seed: seed.map(Into::into),
+		})?;
+		P::try_from(&public[..]).map_err(|_| Error::ValidationError("invalid public key".into()))
+	}
+}
+
+/// Connect to the first address `address` resolves to that accepts within [`TIMEOUT`].
+fn connect_tcp(address: &str) -> io::Result<TcpStream> {
+	let mut error = io::Error::new(io::ErrorKind::NotFound, format!("{} has no address", address));
+	for address in address.to_socket_addrs()? {
+		match TcpStream::connect_timeout(&address, TIMEOUT) {
+			Ok(stream) => return Ok(stream),
+			Err(e) => error = e,
+		}
+	}
+	Err(error)
+}
+
+fn exchange(mut stream: impl Read + Write, line: &[u8]) -> io::Result<String> {
+	stream.write_all(line)?;
+	stream.flush()?;
+	let mut answer = String::new();
+	BufReader::new(stream).read_line(&mut answer)?;
+	Ok(answer)
+}
+
+fn key_type(id: KeyTypeId) -> String {
+	String::from_utf8_lossy(&id.0).into_owned()
+}
+
+fn crypto_type(id: CryptoTypeId) -> String {
+	String::from_utf8_lossy(&id.0).into_owned()
+}
+
+impl SyncCryptoStore for RemoteKeystore {
+	fn sr25519_public_keys(&self, id: KeyTypeId) -> Vec<sr25519::Public> {
+		self.public_keys(id, sr25519::CRYPTO_ID)
+	}
+
+	fn sr25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<sr25519::Public, Error> {
+		self.generate_new(id, sr25519::CRYPTO_ID, seed)
+	}
+
+	fn ed25519_public_keys(&self, id: KeyTypeId) -> Vec<ed25519::Public> {
+		self.public_keys(id, ed25519::CRYPTO_ID)
+	}
+
+	fn ed25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ed25519::Public, Error> {
+		self.generate_new(id, ed25519::CRYPTO_ID, seed)
+	}
+
+	fn ecdsa_public_keys(&self, id: KeyTypeId) -> Vec<ecdsa::Public> {
+		self.public_keys(id, ecdsa::CRYPTO_ID)
+	}
+
+	fn ecdsa_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ecdsa::Public, Error> {
+		self.generate_new(id, ecdsa::CRYPTO_ID, seed)
+	}
+
+	fn insert_unknown(&self, id: KeyTypeId, suri: &str, public: &[u8]) -> Result<(), ()> {
+		self.call(Request::Insert {
+			key_type: key_type(id),
+			suri: suri.into(),
+			public: public.to_vec().into(),
+		})
+		.map_err(|e| log::warn!(target: "remote-keystore", "Inserting a key failed: {}", e))
+	}
+
+	fn keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		let keys: Vec<Key> = self.call(Request::Keys { key_type: key_type(id) })?;
+		keys.into_iter()
+			.map(|key| {
+				let crypto = key.crypto.as_bytes().try_into().map_err(|_| {
+					Error::ValidationError(format!("invalid crypto type {}", key.crypto))
+				})?;
+				Ok(CryptoTypePublicPair(CryptoTypeId(crypto), key.public.0))
+			})
+			.collect()
+	}
+
+	fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		let keys = public_keys
+			.iter()
+			.map(|(public, id)| (key_type(*id), public.clone().into()))
+			.collect();
+		self.call(Request::HasKeys { keys }).unwrap_or(false)
+	}
+
+	fn sign_with(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+	) -> Result<Option<Vec<u8>>, Error> {
+		let signature: Option<Bytes> = self.call(Request::Sign {
+			key_type: key_type(id),
+			crypto: crypto_type(key.0),
+			public: key.1.clone().into(),
+			message: msg.to_vec().into(),
+		})?;
+		Ok(signature.map(|signature| signature.0))
+	}
+
+	fn sr25519_vrf_sign(
+		&self,
+		_key_type: KeyTypeId,
+		_public: &sr25519::Public,
+		_transcript_data: VRFTranscriptData,
+	) -> Result<Option<VRFSignature>, Error> {
+		// Only BABE needs VRFs, this chain runs Aura.
+		Err(Error::Other("VRF signing is not supported by the remote keystore".into()))
+	}
+
+	fn ecdsa_sign_prehashed(
+		&self,
+		id: KeyTypeId,
+		public: &ecdsa::Public,
+		msg: &[u8; 32],
+	) -> Result<Option<ecdsa::Signature>, Error> {
+		let signature: Option<Bytes> = self.call(Request::EcdsaSignPrehashed {
+			key_type: key_type(id),
+			public: public.0.to_vec().into(),
+			message: msg.to_vec().into(),
+		})?;
+		signature
+			.map(|signature| {
+				ecdsa::Signature::try_from(&signature[..])
+					.map_err(|_| Error::ValidationError("invalid ECDSA signature".into()))
+			})
+			.transpose()
+	}
+}
+
+/// The async keystore API, answered by the blocking calls on the blocking thread pool.
+#[async_trait::async_trait]
+impl CryptoStore for RemoteKeystore {
+	async fn sr25519_public_keys(&self, id: KeyTypeId) -> Vec<sr25519::Public> {
+		self.blocking(move |keystore| SyncCryptoStore::sr25519_public_keys(keystore, id))
+			.await
+	}
+
+	async fn sr25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<sr25519::Public, Error> {
+		let seed = seed.map(str::to_owned);
+		self.blocking(move |keystore| {
+			SyncCryptoStore::sr25519_generate_new(keystore, id, seed.as_deref())
+		})
+		.await
+	}
+
+	async fn ed25519_public_keys(&self, id: KeyTypeId) -> Vec<ed25519::Public> {
+		self.blocking(move |keystore| SyncCryptoStore::ed25519_public_keys(keystore, id))
+			.await
+	}
+
+	async fn ed25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ed25519::Public, Error> {
+		let seed = seed.map(str::to_owned);
+		self.blocking(move |keystore| {
+			SyncCryptoStore::ed25519_generate_new(keystore, id, seed.as_deref())
+		})
+		.await
+	}
+
+	async fn ecdsa_public_keys(&self, id: KeyTypeId) -> Vec<ecdsa::Public> {
+		self.blocking(move |keystore| SyncCryptoStore::ecdsa_public_keys(keystore, id))
+			.await
+	}
+
+	async fn ecdsa_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ecdsa::Public, Error> {
+		let seed = seed.map(str::to_owned);
+		self.blocking(move |keystore| {
+			SyncCryptoStore::ecdsa_generate_new(keystore, id, seed.as_deref())
+		})
+		.await
+	}
+
+	async fn insert_unknown(&self, id: KeyTypeId, suri: &str, public: &[u8]) -> Result<(), ()> {
+		let (suri, public) = (suri.to_owned(), public.to_vec());
+		self.blocking(move |keystore| SyncCryptoStore::insert_unknown(keystore, id, &suri, &public))
+			.await
+	}
+
+	async fn keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		self.blocking(move |keystore| SyncCryptoStore::keys(keystore, id)).await
+	}
+
+	async fn supported_keys(
+		&self,
+		id: KeyTypeId,
+		keys: Vec<CryptoTypePublicPair>,
+	) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		self.blocking(move |keystore| SyncCryptoStore::supported_keys(keystore, id, keys))
+			.await
+	}
+
+	async fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		let public_keys = public_keys.to_vec();
+		self.blocking(move |keystore| SyncCryptoStore::has_keys(keystore, &public_keys))
+			.await
+	}
+
+	async fn sign_with(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+	) -> Result<Option<Vec<u8>>, Error> {
+		let (key, msg) = (key.clone(), msg.to_vec());
+		self.blocking(move |keystore| SyncCryptoStore::sign_with(keystore, id, &key, &msg))
+			.await
+	}
+
+	async fn sr25519_vrf_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &sr25519::Public,
+		transcript_data: VRFTranscriptData,
+	) -> Result<Option<VRFSignature>, Error> {
+		// Answered without asking the daemon.
+		SyncCryptoStore::sr25519_vrf_sign(self, key_type, public, transcript_data)
+	}
+
+	async fn ecdsa_sign_prehashed(
+		&self,
+		id: KeyTypeId,
+		public: &ecdsa::Public,
+		msg: &[u8; 32],
+	) -> Result<Option<ecdsa::Signature>, Error> {
+		let (public, msg) = (*public, *msg);
+		self.blocking(move |keystore| {
+			SyncCryptoStore::ecdsa_sign_prehashed(keystore, id, &public, &msg)
+		})
+		.await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sc_keystore::LocalKeystore;
+	use sp_core::{
+		crypto::key_types::{AURA, GRANDPA},
+		Pair,
+	};
+	use std::{net::TcpListener, thread};
+	use tempfile::TempDir;
+
+	/// A signing daemon serving on a free local port.
+	struct Daemon {
+		keystore: Arc<LocalKeystore>,
+		uri: String,
+		_dir: TempDir,
+	}
+
+	fn stand_in() -> Daemon {
+		let dir = tempfile::tempdir().unwrap();
+		let keystore = Arc::new(LocalKeystore::open(dir.path(), None).unwrap());
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let uri = format!("tcp://{}", listener.local_addr().unwrap());
+
+		let local = keystore.clone();
+		thread::spawn(move || {
+			for stream in listener.incoming() {
+				let stream = stream.unwrap();
+				let mut line = String::new();
+				BufReader::new(&stream).read_line(&mut line).unwrap();
+				let response = serde_json::from_str(&line)
+					.map_err(|e| e.to_string())
+					.and_then(|request| answer(&local, request))
+					.map_or_else(Response::Error, Response::Result);
+				let mut line = serde_json::to_vec(&response).unwrap();
+				line.push(b'\n');
+				(&stream).write_all(&line).unwrap();
+			}
+		});
+
+		Daemon { keystore, uri, _dir: dir }
+	}
+
+	fn answer(keystore: &LocalKeystore, request: Request) -> Result<serde_json::Value, String> {
+		fn id(name: &str) -> Result<KeyTypeId, String> {
+			KeyTypeId::try_from(name).map_err(|_| format!("invalid key type {}", name))
+		}
+		fn bytes<K: AsRef<[u8]>>(keys: Vec<K>) -> Vec<Bytes> {
+			keys.iter().map(|key| key.as_ref().to_vec().into()).collect()
+		}
+		fn json(value: impl Serialize) -> Result<serde_json::Value, String> {
+			serde_json::to_value(value).map_err(|e| e.to_string())
+		}
+
+		match request {
+			Request::PublicKeys { key_type, crypto } => {
+				let id = id(&key_type)?;
+				match crypto.as_str() {
+					"sr25" => json(bytes(SyncCryptoStore::sr25519_public_keys(keystore, id))),
+					"ed25" => json(bytes(SyncCryptoStore::ed25519_public_keys(keystore, id))),
+					"ecds" => json(bytes(SyncCryptoStore::ecdsa_public_keys(keystore, id))),
+					_ => Err(format!("unsupported crypto type {}", crypto)),
+				}
+			},
+			Request::GenerateNew { key_type, crypto, seed } => {
+				let (id, seed) = (id(&key_type)?, seed.as_deref());
+				let public = match crypto.as_str() {
+					"sr25" => SyncCryptoStore::sr25519_generate_new(keystore, id, seed)
+						.map(|public| public.0.to_vec()),
+					"ed25" => SyncCryptoStore::ed25519_generate_new(keystore, id, seed)
+						.map(|public| public.0.to_vec()),
+					"ecds" => SyncCryptoStore::ecdsa_generate_new(keystore, id, seed)
+						.map(|public| public.0.to_vec()),
+					_ => return Err(format!("unsupported crypto type {}", crypto)),
+				};
+				json(Bytes(public.map_err(|e| e.to_string())?))
+			},
+			Request::Insert { key_type, suri, public } =>
+				SyncCryptoStore::insert_unknown(keystore, id(&key_type)?, &suri, &public)
+					.map_err(|()| "insertion failed".to_string())
+					.and_then(|()| json(())),
+			Request::Keys { key_type } => {
+				let keys = SyncCryptoStore::keys(keystore, id(&key_type)?)
+					.map_err(|e| e.to_string())?
+					.into_iter()
+					.map(|key| Key { crypto: crypto_type(key.0), public: key.1.into() })
+					.collect::<Vec<_>>();
+				json(keys)
+			},
+			Request::HasKeys { keys } => {
+				let keys = keys
+					.into_iter()
+					.map(|(key_type, public)| Ok((public.0, id(&key_type)?)))
+					.collect::<Result<Vec<_>, String>>()?;
+				json(SyncCryptoStore::has_keys(keystore, &keys))
+			},
+			Request::Sign { key_type, crypto, public, message } => {
+				let crypto = crypto.as_bytes().try_into().map_err(|_| "invalid crypto type")?;
+				let key = CryptoTypePublicPair(CryptoTypeId(crypto), public.0);
+				let signature =
+					SyncCryptoStore::sign_with(keystore, id(&key_type)?, &key, &message)
+						.map_err(|e| e.to_string())?;
+				json(signature.map(Bytes))
+			},
+			Request::EcdsaSignPrehashed { key_type, public, message } => {
+				let id = id(&key_type)?;
+				let public =
+					ecdsa::Public::try_from(&public[..]).map_err(|_| "invalid public key")?;
+				let message = message[..].try_into().map_err(|_| "message is not a hash")?;
+				let signature =
+					SyncCryptoStore::ecdsa_sign_prehashed(keystore, id, &public, message)
+						.map_err(|e| e.to_string())?;
+				json(signature.map(|signature| Bytes(signature.0.to_vec())))
+			},
+		}
+	}
+
+	#[test]
+	fn keys_generated_remotely_sign_remotely() {
+		let daemon = stand_in();
+		let keystore = RemoteKeystore::open(&daemon.uri).unwrap();
+
+		let aura = SyncCryptoStore::sr25519_generate_new(&*keystore, AURA, None).unwrap();
+		let grandpa =
+			SyncCryptoStore::ed25519_generate_new(&*keystore, GRANDPA, Some("//Alice")).unwrap();
+		assert_eq!(SyncCryptoStore::sr25519_public_keys(&*keystore, AURA), vec![aura]);
+		assert_eq!(SyncCryptoStore::sr25519_public_keys(&*daemon.keystore, AURA), vec![aura]);
+		assert_eq!(grandpa, ed25519::Pair::from_string("//Alice", None).unwrap().public());
+
+		let message = b"block header";
+		let signature = SyncCryptoStore::sign_with(&*keystore, AURA, &aura.into(), message)
+			.unwrap()
+			.unwrap();
+		let signature = sr25519::Signature::try_from(&signature[..]).unwrap();
+		assert!(sr25519::Pair::verify(&signature, message, &aura));
+
+		let signature = SyncCryptoStore::sign_with(&*keystore, GRANDPA, &grandpa.into(), message)
+			.unwrap()
+			.unwrap();
+		let signature = ed25519::Signature::try_from(&signature[..]).unwrap();
+		assert!(ed25519::Pair::verify(&signature, message, &grandpa));
+	}
+
+	#[test]
+	fn keys_inserted_remotely_are_known() {
+		let daemon = stand_in();
+		let keystore = RemoteKeystore::open(&daemon.uri).unwrap();
+		let pair = sr25519::Pair::from_string("//Bob", None).unwrap();
+		let public = pair.public();
+
+		let known = |keystore: &RemoteKeystore| {
+			SyncCryptoStore::has_keys(keystore, &[(public.0.to_vec(), AURA)])
+		};
+
+		assert!(!known(&*keystore));
+		assert_eq!(
+			SyncCryptoStore::insert_unknown(&*keystore, AURA, "//Bob", public.as_ref()),
+			Ok(())
+		);
+		assert!(known(&*keystore));
+		assert!(SyncCryptoStore::keys(&*keystore, AURA).unwrap().contains(&public.into()));
+		assert_eq!(
+			SyncCryptoStore::sign_with(&*keystore, GRANDPA, &public.into(), b"vote").unwrap(),
+			None
+		);
+	}
+
+	#[test]
+	fn ecdsa_hashes_are_signed_remotely() {
+		let daemon = stand_in();
+		let keystore = RemoteKeystore::open(&daemon.uri).unwrap();
+		let public = SyncCryptoStore::ecdsa_generate_new(&*keystore, AURA, None).unwrap();
+		let hash = sp_core::blake2_256(b"message");
+
+		let signature = SyncCryptoStore::ecdsa_sign_prehashed(&*keystore, AURA, &public, &hash)
+			.unwrap()
+			.unwrap();
+		assert!(ecdsa::Pair::verify_prehashed(&signature, &hash, &public));
+	}
+
+	#[test]
+	fn async_calls_reach_the_daemon() {
+		let daemon = stand_in();
+		let keystore = RemoteKeystore::open(&daemon.uri).unwrap();
+		let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+
+		let public = runtime
+			.block_on(CryptoStore::sr25519_generate_new(&*keystore, AURA, None))
+			.unwrap();
+		assert_eq!(
+			runtime.block_on(CryptoStore::sr25519_public_keys(&*keystore, AURA)),
+			vec![public]
+		);
+	}
+
+	#[test]
+	fn unreachable_daemon_is_unavailable() {
+		let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+		let keystore = RemoteKeystore::open(&format!("tcp://{}", address)).unwrap();
+
+		assert!(matches!(
+			SyncCryptoStore::sr25519_generate_new(&*keystore, AURA, None),
+			Err(Error::Unavailable)
+		));
+		assert!(SyncCryptoStore::sr25519_public_keys(&*keystore, AURA).is_empty());
+		assert!(RemoteKeystore::open("https://signer.example").is_err());
+	}
+}
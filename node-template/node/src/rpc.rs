@@ -5,14 +5,37 @@
 
 #![warn(missing_docs)]
 
-use std::sync::Arc;
+use std::{marker::PhantomData, sync::Arc};
 
-use jsonrpsee::RpcModule;
-use node_template_runtime::{opaque::Block, AccountId, Balance, Index};
+use crate::{
+	events::{block_events, EventRecord},
+	indexer::{Indexer, IndexerApiServer, IndexerRpc},
+};
+use codec::Decode;
+use futures::{
+	channel::mpsc,
+	future,
+	stream::{self, BoxStream},
+	FutureExt, Stream, StreamExt,
+};
+use jsonrpsee::{proc_macros::rpc, types::SubscriptionResult, RpcModule, SubscriptionSink};
+use node_template_client::keys;
+use node_template_runtime::{
+	opaque::Block, AccountId, Balance, BlockNumber, Event, Hash, Index, Runtime,
+};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use sc_consensus_manual_seal::{
+	rpc::{ManualSeal, ManualSealApiServer},
+	EngineCommand,
+};
+use sc_rpc::SubscriptionTaskExecutor;
 use sc_transaction_pool_api::TransactionPool;
+use serde::Serialize;
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
+use sp_core::{storage::StorageKey, Bytes};
+use sp_runtime::generic::BlockId;
 
 pub use sc_rpc_api::DenyUnsafe;
 
@@ -24,29 +47,50 @@ pub struct FullDeps<C, P> {
 	pub pool: Arc<P>,
 	/// Whether to deny unsafe calls
 	pub deny_unsafe: DenyUnsafe,
+	/// Where the `engine_*` RPCs send their commands when blocks are sealed manually.
+	pub command_sink: Option<mpsc::Sender<EngineCommand<Hash>>>,
+	/// The kitty and PoE event index, when the node keeps one.
+	pub indexer: Option<Arc<Indexer>>,
+	/// Where subscriptions run.
+	pub subscription_executor: SubscriptionTaskExecutor,
 }
 
 /// Instantiate all full RPC extensions.
-pub fn create_full<C, P>(
+pub fn create_full<C, P, B>(
 	deps: FullDeps<C, P>,
 ) -> Result<RpcModule<()>, Box<dyn std::error::Error + Send + Sync>>
 where
+	B: Backend<Block> + 'static,
 	C: ProvideRuntimeApi<Block>,
 	C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError> + 'static,
+	C: BlockchainEvents<Block> + StorageProvider<Block, B>,
 	C: Send + Sync + 'static,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+	C::Api: pallet_contracts_rpc::ContractsRuntimeApi<Block, AccountId, Balance, BlockNumber, Hash>,
 	C::Api: BlockBuilder<Block>,
 	P: TransactionPool + 'static,
 {
+	use pallet_contracts_rpc::{Contracts, ContractsApiServer};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
 	use substrate_frame_rpc_system::{System, SystemApiServer};
 
 	let mut module = RpcModule::new(());
-	let FullDeps { client, pool, deny_unsafe } = deps;
+	let FullDeps { client, pool, deny_unsafe, command_sink, indexer, subscription_executor } = deps;
 
 	module.merge(System::new(client.clone(), pool.clone(), deny_unsafe).into_rpc())?;
-	module.merge(TransactionPayment::new(client).into_rpc())?;
+	module.merge(TransactionPayment::new(client.clone()).into_rpc())?;
+	module.merge(Contracts::new(client.clone()).into_rpc())?;
+	let chain = Arc::new(ClientChain::<C, B>::new(client));
+	module.merge(KittySubscriptions::new(chain, subscription_executor).into_rpc())?;
+
+	if let Some(command_sink) = command_sink {
+		module.merge(ManualSeal::new(command_sink).into_rpc())?;
+	}
+
+	if let Some(indexer) = indexer {
+		module.merge(IndexerRpc::new(indexer).into_rpc())?;
+	}
 
 	// Extend this RPC with a custom API by using the following syntax.
 	// `YourRpcStruct` should have a reference to a client, which is needed
@@ -55,3 +99,520 @@ where
 
 	Ok(module)
 }
+
+/// A change to a kitty's ownership or price.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub enum KittyActivity {
+	/// The owner put their kitty on sale, or off sale without a price.
+	PriceSet { owner: AccountId, price: Option<Balance> },
+	/// The kitty was given away.
+	Transferred { from: AccountId, to: AccountId },
+	/// The kitty was bought.
+	Sold { seller: AccountId, buyer: AccountId, price: Balance },
+}
+
+impl KittyActivity {
+	/// Whether `account` owned the kitty before or after the activity.
+	fn concerns(&self, account: &AccountId) -> bool {
+		match self {
+			KittyActivity::PriceSet { owner, .. } => owner == account,
+			KittyActivity::Transferred { from, to } => from == account || to == account,
+			KittyActivity::Sold { seller, buyer, .. } => seller == account || buyer == account,
+		}
+	}
+}
+
+/// A kitty activity found in a block, as streamed to subscribers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct KittyNotification {
+	pub block_hash: Hash,
+	pub block_number: BlockNumber,
+	/// The DNA of the kitty.
+	pub kitty: Bytes,
+	pub activity: KittyActivity,
+}
+
+/// Subscriptions to the kitty activity of new blocks, so clients don't have to poll.
+///
+/// By default a block is streamed as soon as it is imported as the new best block, including the
+/// blocks enacted by a reorg: activity of a block that is later retracted may be streamed. With
+/// `finalized`, only finalized blocks are streamed.
+#[rpc(server, namespace = "kitties")]
+pub trait KittiesApi {
+	/// The transfers and sales an account was part of and the prices it set.
+	#[subscription(
+		name = "subscribeOwner" => "owner",
+		unsubscribe = "unsubscribeOwner",
+		item = KittyNotification
+	)]
+	fn subscribe_owner(&self, account: AccountId, finalized: Option<bool>);
+
+	/// Every kitty sale.
+	#[subscription(
+		name = "subscribeSales" => "sale",
+		unsubscribe = "unsubscribeSales",
+		item = KittyNotification
+	)]
+	fn subscribe_sales(&self, finalized: Option<bool>);
+}
+
+/// What the kitty subscriptions follow: the blocks of a chain and the kitties in their state.
+pub trait KittyChain: Send + Sync + 'static {
+	/// Every new best block, preceded by the blocks a reorg enacts before it.
+	fn best_blocks(&self) -> BoxStream<'static, Vec<Hash>>;
+
+	/// Every newly finalized block, preceded by the blocks it finalizes along.
+	fn finalized_blocks(&self) -> BoxStream<'static, Vec<Hash>>;
+
+	/// The number of the block `hash`.
+	fn block_number(&self, hash: Hash) -> sp_blockchain::Result<Option<BlockNumber>>;
+
+	/// The events deposited in the block `hash`.
+	fn events(&self, hash: Hash) -> sp_blockchain::Result<Vec<EventRecord>>;
+
+	/// The owner of the kitty with DNA `kitty` at the end of the block `hash`.
+	fn kitty_owner(&self, hash: Hash, kitty: &[u8; 16])
+		-> sp_blockchain::Result<Option<AccountId>>;
+}
+
+/// The [`KittyChain`] of a full client.
+pub struct ClientChain<C, B> {
+	client: Arc<C>,
+	_backend: PhantomData<B>,
+}
+
+impl<C, B> ClientChain<C, B> {
+	/// The chain of `client`.
+	pub fn new(client: Arc<C>) -> Self {
+		ClientChain { client, _backend: PhantomData }
+	}
+}
+
+impl<C, B> KittyChain for ClientChain<C, B>
+where
+	B: Backend<Block> + 'static,
+	C: BlockchainEvents<Block> + StorageProvider<Block, B> + HeaderBackend<Block>,
+	C: Send + Sync + 'static,
+{
+	fn best_blocks(&self) -> BoxStream<'static, Vec<Hash>> {
+		self.client
+			.import_notification_stream()
+			.filter(|notification| future::ready(notification.is_new_best))
+			.map(|notification| {
+				let mut hashes: Vec<Hash> =
+					notification.tree_route.map_or_else(Vec::new, |route| {
+						route.enacted().iter().map(|block| block.hash).collect()
+					});
+				hashes.push(notification.hash);
+				hashes
+			})
+			.boxed()
+	}
+
+	fn finalized_blocks(&self) -> BoxStream<'static, Vec<Hash>> {
+		self.client
+			.finality_notification_stream()
+			.map(|notification| {
+				let mut hashes = notification.tree_route.to_vec();
+				hashes.push(notification.hash);
+				hashes
+			})
+			.boxed()
+	}
+
+	fn block_number(&self, hash: Hash) -> sp_blockchain::Result<Option<BlockNumber>> {
+		self.client.number(hash)
+	}
+
+	fn events(&self, hash: Hash) -> sp_blockchain::Result<Vec<EventRecord>> {
+		block_events(&*self.client, hash)
+	}
+
+	fn kitty_owner(
+		&self,
+		hash: Hash,
+		kitty: &[u8; 16],
+	) -> sp_blockchain::Result<Option<AccountId>> {
+		let data = self.client.storage(&BlockId::Hash(hash), &StorageKey(keys::kitty(kitty)))?;
+		Ok(data
+			.and_then(|data| pallet_kitties::Kitty::<Runtime>::decode(&mut &data.0[..]).ok())
+			.map(|kitty| kitty.owner))
+	}
+}
+
+/// The `kitties_subscribe*` RPCs.
+pub struct KittySubscriptions<C> {
+	chain: Arc<C>,
+	executor: SubscriptionTaskExecutor,
+}
+
+impl<C: KittyChain> KittySubscriptions<C> {
+	/// Subscriptions following the blocks of `chain`, run by `executor`.
+	pub fn new(chain: Arc<C>, executor: SubscriptionTaskExecutor) -> Self {
+		KittySubscriptions { chain, executor }
+	}
+
+	/// The kitty activity of every new best block, or of every finalized block.
+	fn activity(&self, finalized: bool) -> impl Stream<Item = KittyNotification> + Send {
+		let blocks =
+			if finalized { self.chain.finalized_blocks() } else { self.chain.best_blocks() };
+
+		let chain = self.chain.clone();
+		blocks.flat_map(move |hashes| {
+			let notifications = hashes.into_iter().flat_map(|hash| {
+				block_activity(&*chain, hash).unwrap_or_else(|e| {
+					log::warn!(target: "rpc", "Kitty activity of block {} skipped: {}", hash, e);
+					Vec::new()
+				})
+			});
+			stream::iter(notifications.collect::<Vec<_>>())
+		})
+	}
+
+	fn pipe(
+		&self,
+		mut sink: SubscriptionSink,
+		notifications: impl Stream<Item = KittyNotification> + Send + Unpin + 'static,
+	) {
+		let fut = async move {
+			sink.pipe_from_stream(notifications).await;
+		};
+		self.executor.spawn("kitties-rpc-subscription", Some("rpc"), fut.boxed());
+	}
+}
+
+impl<C: KittyChain> KittiesApiServer for KittySubscriptions<C> {
+	fn subscribe_owner(
+		&self,
+		sink: SubscriptionSink,
+		account: AccountId,
+		finalized: Option<bool>,
+	) -> SubscriptionResult {
+		let notifications = self.activity(finalized.unwrap_or_default());
+		let notifications = notifications
+			.filter(move |notification| future::ready(notification.activity.concerns(&account)));
+		self.pipe(sink, notifications.boxed());
+		Ok(())
+	}
+
+	fn subscribe_sales(
+		&self,
+		sink: SubscriptionSink,
+		finalized: Option<bool>,
+	) -> SubscriptionResult {
+		let notifications = self.activity(finalized.unwrap_or_default()).filter(|notification| {
+			future::ready(matches!(notification.activity, KittyActivity::Sold { .. }))
+		});
+		self.pipe(sink, notifications.boxed());
+		Ok(())
+	}
+}
+
+/// The kitty activity of the block `hash`.
+fn block_activity(
+	chain: &impl KittyChain,
+	hash: Hash,
+) -> sp_blockchain::Result<Vec<KittyNotification>> {
+	let block_number = chain
+		.block_number(hash)?
+		.ok_or_else(|| BlockChainError::UnknownBlock(format!("Block {}", hash)))?;
+	let notification = |kitty: [u8; 16], activity| KittyNotification {
+		block_hash: hash,
+		block_number,
+		kitty: kitty.to_vec().into(),
+		activity,
+	};
+
+	let mut notifications = Vec::new();
+	// The kitty sold by the last sale and its phase: buying a kitty transfers it too.
+	let mut sold = None;
+	let events = chain.events(hash)?;
+	for (index, record) in events.iter().enumerate() {
+		let activity = match record.event.clone() {
+			Event::SubstrateKitties(pallet_kitties::Event::PriceSet { kitty, price }) => {
+				let owner = match owner_before(&events[index + 1..], &kitty) {
+					Some(owner) => Some(owner),
+					None => chain.kitty_owner(hash, &kitty)?,
+				};
+				owner.map(|owner| (kitty, KittyActivity::PriceSet { owner, price }))
+			},
+			Event::SubstrateKitties(pallet_kitties::Event::Transferred { from, to, kitty }) =>
+				(sold != Some((record.phase.clone(), kitty)))
+					.then(|| (kitty, KittyActivity::Transferred { from, to })),
+			Event::SubstrateKitties(pallet_kitties::Event::Sold {
+				seller,
+				buyer,
+				kitty,
+				price,
+			}) => {
+				sold = Some((record.phase.clone(), kitty));
+				Some((kitty, KittyActivity::Sold { seller, buyer, price }))
+			},
+			_ => None,
+		};
+		notifications.extend(activity.map(|(kitty, activity)| notification(kitty, activity)));
+	}
+	Ok(notifications)
+}
+
+/// Who owned `kitty` before the transfers of `events`, if any of them moves it.
+fn owner_before(events: &[EventRecord], kitty: &[u8; 16]) -> Option<AccountId> {
+	events.iter().find_map(|record| match &record.event {
+		Event::SubstrateKitties(pallet_kitties::Event::Transferred {
+			from, kitty: moved, ..
+		}) if moved == kitty => Some(from.clone()),
+		_ => None,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_system::Phase;
+	use futures::executor::block_on;
+	use jsonrpsee::core::server::rpc_module::Subscription;
+	use sp_keyring::Sr25519Keyring::{Alice, Bob, Charlie};
+	use std::{collections::HashMap, sync::Mutex, thread, time::Duration};
+
+	type Followers = Mutex<Vec<mpsc::UnboundedSender<Vec<Hash>>>>;
+
+	/// A chain in memory whose blocks only have a number and events. A kitty is owned by whom
+	/// the block last transferred it to.
+	#[derive(Default)]
+	struct TestChain {
+		blocks: Mutex<HashMap<Hash, (BlockNumber, Vec<EventRecord>)>>,
+		best: Followers,
+		finalized: Followers,
+	}
+
+	impl TestChain {
+		/// Import block `number` as the new best block, finalized if `finalize`. Every item of
+		/// `extrinsics` holds the events of one extrinsic.
+		fn import(&self, number: BlockNumber, extrinsics: Vec<Vec<Event>>, finalize: bool) -> Hash {
+			let hash = Hash::repeat_byte(number as u8);
+			let events = extrinsics
+				.into_iter()
+				.enumerate()
+				.flat_map(|(index, events)| {
+					events.into_iter().map(move |event| EventRecord {
+						phase: Phase::ApplyExtrinsic(index as u32),
+						event,
+						topics: vec![],
+					})
+				})
+				.collect();
+			self.blocks.lock().unwrap().insert(hash, (number, events));
+
+			let notify = |followers: &Followers| {
+				followers.lock().unwrap().retain(|sink| sink.unbounded_send(vec![hash]).is_ok())
+			};
+			notify(&self.best);
+			if finalize {
+				notify(&self.finalized);
+			}
+			hash
+		}
+
+		/// The number of block streams still followed by a subscription.
+		fn followers(&self) -> usize {
+			[&self.best, &self.finalized]
+				.iter()
+				.map(|followers| {
+					let mut followers = followers.lock().unwrap();
+					followers.retain(|sink| !sink.is_closed());
+					followers.len()
+				})
+				.sum()
+		}
+
+		fn follow(followers: &Followers) -> BoxStream<'static, Vec<Hash>> {
+			let (sink, stream) = mpsc::unbounded();
+			followers.lock().unwrap().push(sink);
+			stream.boxed()
+		}
+	}
+
+	impl KittyChain for TestChain {
+		fn best_blocks(&self) -> BoxStream<'static, Vec<Hash>> {
+			Self::follow(&self.best)
+		}
+
+		fn finalized_blocks(&self) -> BoxStream<'static, Vec<Hash>> {
+			Self::follow(&self.finalized)
+		}
+
+		fn block_number(&self, hash: Hash) -> sp_blockchain::Result<Option<BlockNumber>> {
+			Ok(self.blocks.lock().unwrap().get(&hash).map(|(number, _)| *number))
+		}
+
+		fn events(&self, hash: Hash) -> sp_blockchain::Result<Vec<EventRecord>> {
+			Ok(self
+				.blocks
+				.lock()
+				.unwrap()
+				.get(&hash)
+				.map_or_else(Vec::new, |(_, events)| events.clone()))
+		}
+
+		fn kitty_owner(
+			&self,
+			hash: Hash,
+			kitty: &[u8; 16],
+		) -> sp_blockchain::Result<Option<AccountId>> {
+			// The last owner the block gave the kitty to.
+			let owner =
+				self.events(hash)?.into_iter().rev().find_map(|record| match record.event {
+					Event::SubstrateKitties(pallet_kitties::Event::Transferred {
+						to,
+						kitty: transferred,
+						..
+					}) if &transferred == kitty => Some(to),
+					_ => None,
+				});
+			Ok(owner)
+		}
+	}
+
+	fn sold(seller: &AccountId, buyer: &AccountId) -> Event {
+		let (seller, buyer) = (seller.clone(), buyer.clone());
+		pallet_kitties::Event::<Runtime>::Sold { seller, buyer, kitty: [7; 16], price: 10 }.into()
+	}
+
+	fn transferred(from: &AccountId, to: &AccountId) -> Event {
+		let (from, to) = (from.clone(), to.clone());
+		pallet_kitties::Event::<Runtime>::Transferred { from, to, kitty: [7; 16] }.into()
+	}
+
+	fn subscriptions(chain: &Arc<TestChain>) -> RpcModule<KittySubscriptions<TestChain>> {
+		let executor: SubscriptionTaskExecutor = Arc::new(sp_core::testing::TaskExecutor::new());
+		KittySubscriptions::new(chain.clone(), executor).into_rpc()
+	}
+
+	fn next(sub: &mut Subscription) -> serde_json::Value {
+		block_on(sub.next::<serde_json::Value>()).unwrap().unwrap().0
+	}
+
+	fn json(hash: &Hash) -> serde_json::Value {
+		serde_json::to_value(hash).unwrap()
+	}
+
+	#[test]
+	fn subscriptions_follow_new_and_finalized_blocks() {
+		let chain = Arc::new(TestChain::default());
+		let rpc = subscriptions(&chain);
+		let (alice, bob, charlie) =
+			(Alice.to_account_id(), Bob.to_account_id(), Charlie.to_account_id());
+
+		let mut owner = block_on(rpc.subscribe("kitties_subscribeOwner", [&charlie])).unwrap();
+		let mut sales = block_on(rpc.subscribe("kitties_subscribeSales", [false])).unwrap();
+		let mut finalized_sales =
+			block_on(rpc.subscribe("kitties_subscribeSales", [true])).unwrap();
+
+		let first = chain.import(1, vec![vec![sold(&alice, &bob)]], false);
+		let second = chain.import(
+			2,
+			vec![vec![sold(&bob, &charlie)], vec![transferred(&charlie, &bob)]],
+			true,
+		);
+
+		let sale = next(&mut sales);
+		assert_eq!(sale["blockNumber"], 1);
+		assert_eq!(sale["blockHash"], json(&first));
+		assert_eq!(next(&mut sales)["blockNumber"], 2);
+		assert_eq!(next(&mut owner)["activity"]["sold"]["price"], 10);
+		assert!(next(&mut owner)["activity"]["transferred"].is_object());
+		// Only the second block was finalized.
+		assert_eq!(next(&mut finalized_sales)["blockHash"], json(&second));
+
+		for sub in [&mut owner, &mut sales, &mut finalized_sales] {
+			sub.close();
+			assert!(block_on(sub.next::<serde_json::Value>()).is_none());
+		}
+		// The subscription tasks stop following the chain once unsubscribed.
+		for _ in 0..100 {
+			if chain.followers() == 0 {
+				break
+			}
+			thread::sleep(Duration::from_millis(10));
+		}
+		assert_eq!(chain.followers(), 0);
+	}
+
+	#[test]
+	fn sales_are_not_streamed_as_transfers_too() {
+		let chain = Arc::new(TestChain::default());
+		let rpc = subscriptions(&chain);
+		let (alice, bob) = (Alice.to_account_id(), Bob.to_account_id());
+		let mut owner = block_on(rpc.subscribe("kitties_subscribeOwner", [&bob])).unwrap();
+
+		// Buying a kitty deposits both events in the same phase.
+		chain.import(1, vec![vec![sold(&alice, &bob), transferred(&alice, &bob)]], false);
+		// A transfer in a later extrinsic is activity of its own.
+		chain.import(2, vec![vec![sold(&alice, &bob)], vec![transferred(&bob, &alice)]], false);
+
+		let first = next(&mut owner);
+		assert_eq!(first["blockNumber"], 1);
+		assert!(first["activity"]["sold"].is_object());
+		let second = next(&mut owner);
+		assert_eq!(second["blockNumber"], 2);
+		assert!(second["activity"]["sold"].is_object());
+		let third = next(&mut owner);
+		assert_eq!(third["blockNumber"], 2);
+		assert!(third["activity"]["transferred"].is_object());
+	}
+
+	#[test]
+	fn prices_are_set_by_the_owner_at_the_time() {
+		let chain = Arc::new(TestChain::default());
+		let rpc = subscriptions(&chain);
+		let (alice, bob) = (Alice.to_account_id(), Bob.to_account_id());
+		let mut sellers = block_on(rpc.subscribe("kitties_subscribeOwner", [&alice])).unwrap();
+		let mut buyers = block_on(rpc.subscribe("kitties_subscribeOwner", [&bob])).unwrap();
+		let price_set = |price| -> Event {
+			pallet_kitties::Event::<Runtime>::PriceSet { kitty: [7; 16], price }.into()
+		};
+
+		// Alice puts the kitty on sale, Bob buys it and takes it off sale in the same block.
+		chain.import(
+			1,
+			vec![
+				vec![price_set(Some(10))],
+				vec![sold(&alice, &bob), transferred(&alice, &bob)],
+				vec![price_set(None)],
+			],
+			false,
+		);
+
+		let on_sale = next(&mut sellers);
+		assert_eq!(on_sale["activity"]["priceSet"]["owner"], serde_json::to_value(&alice).unwrap());
+		assert_eq!(on_sale["activity"]["priceSet"]["price"], 10);
+		assert!(next(&mut sellers)["activity"]["sold"].is_object());
+
+		assert!(next(&mut buyers)["activity"]["sold"].is_object());
+		let off_sale = next(&mut buyers);
+		assert_eq!(off_sale["activity"]["priceSet"]["owner"], serde_json::to_value(&bob).unwrap());
+		assert!(off_sale["activity"]["priceSet"]["price"].is_null());
+	}
+
+	#[test]
+	fn owner_activity_concerns_both_sides() {
+		let (alice, bob) = (Alice.to_account_id(), Bob.to_account_id());
+		let sold = KittyActivity::Sold { seller: alice.clone(), buyer: bob.clone(), price: 10 };
+		assert!(sold.concerns(&alice) && sold.concerns(&bob));
+		assert!(!sold.concerns(&Charlie.to_account_id()));
+
+		let notification = KittyNotification {
+			block_hash: Hash::repeat_byte(1),
+			block_number: 1,
+			kitty: vec![7; 16].into(),
+			activity: KittyActivity::PriceSet { owner: alice, price: None },
+		};
+		let json = serde_json::to_value(&notification).unwrap();
+		assert_eq!(json["blockNumber"], 1);
+		assert!(json["activity"]["priceSet"]["price"].is_null());
+	}
+}
@@ -1,15 +1,33 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
-use node_template_runtime::{self, opaque::Block, RuntimeApi};
-use sc_client_api::{BlockBackend, ExecutorProvider};
+use crate::{cli::Sealing, indexer::Indexer, remote_keystore::RemoteKeystore};
+use codec::Decode;
+use futures::{channel::mpsc, prelude::*};
+use node_template_client::keys;
+use node_template_runtime::{self, opaque::Block, Hash, RuntimeApi};
+use sc_client_api::{BlockBackend, ExecutorProvider, StorageProvider};
 use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
+use sc_consensus_manual_seal::{
+	consensus::aura::AuraConsensusDataProvider, EngineCommand, ManualSealParams,
+};
 pub use sc_executor::NativeElseWasmExecutor;
 use sc_finality_grandpa::SharedVoterState;
-use sc_keystore::LocalKeystore;
-use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
+use sc_service::{error::Error as ServiceError, BasePath, Configuration, TaskManager};
 use sc_telemetry::{Telemetry, TelemetryWorker};
+use sc_transaction_pool_api::TransactionPool;
+use sp_blockchain::HeaderBackend;
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
-use std::{sync::Arc, time::Duration};
+use sp_core::storage::StorageKey;
+use sp_runtime::generic::BlockId;
+use std::{
+	path::PathBuf,
+	pin::Pin,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc,
+	},
+	time::Duration,
+};
 
 // Our native executor instance.
 pub struct ExecutorDispatch;
@@ -33,11 +51,14 @@ impl sc_executor::NativeExecutionDispatch for ExecutorDispatch {
 
 pub(crate) type FullClient =
 	sc_service::TFullClient<Block, RuntimeApi, NativeElseWasmExecutor<ExecutorDispatch>>;
-type FullBackend = sc_service::TFullBackend<Block>;
+pub(crate) type FullBackend = sc_service::TFullBackend<Block>;
 type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
 
+/// The components shared by the node and the commands working on its database. With `sealing`,
+/// imported blocks are expected to be manually sealed instead of authored by Aura.
 pub fn new_partial(
 	config: &Configuration,
+	sealing: Option<Sealing>,
 ) -> Result<
 	sc_service::PartialComponents<
 		FullClient,
@@ -58,10 +79,6 @@ pub fn new_partial(
 	>,
 	ServiceError,
 > {
-	if config.keystore_remote.is_some() {
-		return Err(ServiceError::Other("Remote Keystores are not supported.".into()))
-	}
-
 	let telemetry = config
 		.telemetry_endpoints
 		.clone()
@@ -112,7 +129,13 @@ pub fn new_partial(
 
 	let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
 
-	let import_queue =
+	let import_queue = if sealing.is_some() {
+		sc_consensus_manual_seal::import_queue(
+			Box::new(grandpa_block_import.clone()),
+			&task_manager.spawn_essential_handle(),
+			config.prometheus_registry(),
+		)
+	} else {
 		sc_consensus_aura::import_queue::<AuraPair, _, _, _, _, _, _>(ImportQueueParams {
 			block_import: grandpa_block_import.clone(),
 			justification_import: Some(Box::new(grandpa_block_import.clone())),
@@ -135,7 +158,8 @@ pub fn new_partial(
 			registry: config.prometheus_registry(),
 			check_for_equivocation: Default::default(),
 			telemetry: telemetry.as_ref().map(|x| x.handle()),
-		})?;
+		})?
+	};
 
 	Ok(sc_service::PartialComponents {
 		client,
@@ -149,15 +173,22 @@ pub fn new_partial(
 	})
 }
 
-fn remote_keystore(_url: &String) -> Result<Arc<LocalKeystore>, &'static str> {
-	// FIXME: here would the concrete keystore be built,
-	//        must return a concrete type (NOT `LocalKeystore`) that
-	//        implements `CryptoStore` and `SyncCryptoStore`
-	Err("Remote Keystore not supported.")
+/// The directory of the chain's own databases, next to its keystore and network key.
+pub(crate) fn config_dir(config: &Configuration) -> PathBuf {
+	config
+		.base_path
+		.clone()
+		.unwrap_or_else(|| BasePath::from_project("", "", "node-template"))
+		.config_dir(config.chain_spec.id())
 }
 
-/// Builds a new service for a full client.
-pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError> {
+/// Builds a new service for a full client. With `sealing`, blocks are sealed on demand instead of
+/// by Aura and GRANDPA. With `indexer`, kitty and PoE events are indexed for the `indexer_*` RPCs.
+pub fn new_full(
+	mut config: Configuration,
+	sealing: Option<Sealing>,
+	indexer: bool,
+) -> Result<TaskManager, ServiceError> {
 	let sc_service::PartialComponents {
 		client,
 		backend,
@@ -167,10 +198,10 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		select_chain,
 		transaction_pool,
 		other: (block_import, grandpa_link, mut telemetry),
-	} = new_partial(&config)?;
+	} = new_partial(&config, sealing)?;
 
 	if let Some(url) = &config.keystore_remote {
-		match remote_keystore(url) {
+		match RemoteKeystore::open(url) {
 			Ok(k) => keystore_container.set_remote_keystore(k),
 			Err(e) =>
 				return Err(ServiceError::Other(format!(
@@ -221,14 +252,68 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	let enable_grandpa = !config.disable_grandpa;
 	let prometheus_registry = config.prometheus_registry().cloned();
 
+	#[cfg(feature = "evm")]
+	let frontier =
+		crate::eth::Frontier::new(&config, &task_manager, client.clone(), backend.clone())
+			.map_err(ServiceError::Other)?;
+
+	let indexer = if indexer {
+		let indexer = Indexer::open(&config_dir(&config).join("indexer"))
+			.map_err(|e| ServiceError::Other(e.to_string()))?;
+		let indexer = Arc::new(indexer);
+		task_manager.spawn_handle().spawn_blocking(
+			"indexer",
+			Some("indexer"),
+			crate::indexer::run(indexer.clone(), client.clone()),
+		);
+		Some(indexer)
+	} else {
+		None
+	};
+
+	if let Some(registry) = prometheus_registry.as_ref() {
+		task_manager.spawn_handle().spawn_blocking(
+			"app-metrics",
+			Some("metrics"),
+			crate::metrics::run(crate::metrics::Metrics::register(registry)?, client.clone()),
+		);
+	}
+
+	// `engine_*` RPCs control manual sealing through this channel.
+	let (command_sink, commands_stream) = match sealing {
+		Some(_) => {
+			let (sink, stream) = mpsc::channel(1024);
+			(Some(sink), Some(stream))
+		},
+		None => (None, None),
+	};
+
 	let rpc_extensions_builder = {
 		let client = client.clone();
 		let pool = transaction_pool.clone();
-
-		Box::new(move |deny_unsafe, _| {
-			let deps =
-				crate::rpc::FullDeps { client: client.clone(), pool: pool.clone(), deny_unsafe };
-			crate::rpc::create_full(deps).map_err(Into::into)
+		#[cfg(feature = "evm")]
+		let (network, is_authority) = (network.clone(), role.is_authority());
+
+		Box::new(move |deny_unsafe, subscription_executor| {
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				deny_unsafe,
+				command_sink: command_sink.clone(),
+				indexer: indexer.clone(),
+				subscription_executor,
+			};
+			#[allow(unused_mut)]
+			let mut module = crate::rpc::create_full(deps)?;
+			#[cfg(feature = "evm")]
+			frontier.merge_rpc(
+				&mut module,
+				client.clone(),
+				pool.clone(),
+				network.clone(),
+				is_authority,
+			)?;
+			Ok(module)
 		})
 	};
 
@@ -245,6 +330,22 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		telemetry: telemetry.as_mut(),
 	})?;
 
+	if let (Some(sealing), Some(commands_stream)) = (sealing, commands_stream) {
+		run_manual_seal(
+			sealing,
+			commands_stream,
+			&task_manager,
+			client,
+			transaction_pool,
+			select_chain,
+			block_import,
+			prometheus_registry.as_ref(),
+			telemetry.as_ref(),
+		)?;
+		network_starter.start_network();
+		return Ok(task_manager)
+	}
+
 	if role.is_authority() {
 		let proposer_factory = sc_basic_authorship::ProposerFactory::new(
 			task_manager.spawn_handle(),
@@ -342,3 +443,101 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	network_starter.start_network();
 	Ok(task_manager)
 }
+
+/// Spawn the task sealing blocks on the commands of the `engine_*` RPCs and, depending on
+/// `sealing`, for every new transaction or at a fixed interval.
+#[allow(clippy::too_many_arguments)]
+fn run_manual_seal(
+	sealing: Sealing,
+	rpc_commands: mpsc::Receiver<EngineCommand<Hash>>,
+	task_manager: &TaskManager,
+	client: Arc<FullClient>,
+	transaction_pool: Arc<sc_transaction_pool::FullPool<Block, FullClient>>,
+	select_chain: FullSelectChain,
+	block_import: sc_finality_grandpa::GrandpaBlockImport<
+		FullBackend,
+		Block,
+		FullClient,
+		FullSelectChain,
+	>,
+	prometheus_registry: Option<&prometheus_endpoint::Registry>,
+	telemetry: Option<&Telemetry>,
+) -> Result<(), ServiceError> {
+	// Nothing else finalizes blocks without GRANDPA, so automatic seals finalize their blocks.
+	let seal = || EngineCommand::SealNewBlock {
+		create_empty: true,
+		finalize: true,
+		parent_hash: None,
+		sender: None,
+	};
+	let auto_commands: Pin<Box<dyn Stream<Item = EngineCommand<Hash>> + Send>> = match sealing {
+		Sealing::Instant => Box::pin(transaction_pool.import_notification_stream().map(|_| {
+			EngineCommand::SealNewBlock {
+				create_empty: false,
+				finalize: true,
+				parent_hash: None,
+				sender: None,
+			}
+		})),
+		Sealing::Manual => Box::pin(stream::pending()),
+		Sealing::Interval(millis) => Box::pin(stream::unfold((), move |()| async move {
+			futures_timer::Delay::new(Duration::from_millis(millis)).await;
+			Some((seal(), ()))
+		})),
+	};
+
+	let proposer_factory = sc_basic_authorship::ProposerFactory::new(
+		task_manager.spawn_handle(),
+		client.clone(),
+		transaction_pool.clone(),
+		prometheus_registry,
+		telemetry.map(|x| x.handle()),
+	);
+
+	// Every block needs an Aura slot of its own, so timestamps advance by at least a slot even
+	// when blocks are sealed faster than that. The chain can then run ahead of the clock: carry on
+	// from the timestamp of the best block rather than from now after a restart.
+	let slot_duration = sc_consensus_aura::slot_duration(&*client)?;
+	let slot_millis = slot_duration.as_millis() as u64;
+	let best_timestamp = client
+		.storage(&BlockId::Hash(client.info().best_hash), &StorageKey(keys::timestamp_now()))?
+		.and_then(|data| u64::decode(&mut &data.0[..]).ok())
+		.unwrap_or_default();
+	let last_timestamp = Arc::new(AtomicU64::new(best_timestamp));
+	let create_inherent_data_providers = move |_, ()| {
+		let last_timestamp = last_timestamp.clone();
+		async move {
+			let now = *sp_timestamp::Timestamp::current();
+			let previous = last_timestamp
+				.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+					Some(now.max(last + slot_millis))
+				})
+				.expect("the closure always returns `Some`; qed");
+			let timestamp =
+				sp_timestamp::InherentDataProvider::new(now.max(previous + slot_millis).into());
+			let slot =
+				sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_slot_duration(
+					*timestamp,
+					slot_duration,
+				);
+
+			Ok((timestamp, slot))
+		}
+	};
+
+	let manual_seal = sc_consensus_manual_seal::run_manual_seal(ManualSealParams {
+		block_import,
+		env: proposer_factory,
+		client: client.clone(),
+		pool: transaction_pool,
+		commands_stream: stream::select(rpc_commands, auto_commands),
+		select_chain,
+		consensus_data_provider: Some(Box::new(AuraConsensusDataProvider::new(client))),
+		create_inherent_data_providers,
+	});
+
+	task_manager
+		.spawn_essential_handle()
+		.spawn_blocking("manual-seal", Some("block-authoring"), manual_seal);
+	Ok(())
+}
@@ -0,0 +1,419 @@
+//! The `snapshot` subcommand: the state of a block saved to a file, and a new database started
+//! from such a file instead of syncing every block before it.
+//!
+//! A snapshot holds the header of its block with its justifications and every key of the state,
+//! child tries included. It is SCALE encoded, compressed with zstd and prefixed with a magic number
+//! and the BLAKE2-256 checksum of the compressed data. On restore, the snapshot must be of the
+//! genesis of the node, its header must carry a GRANDPA justification signed by the genesis
+//! authorities, and the state must hash to the state root of the header.
+//!
+//! GRANDPA only stores justifications for some blocks: those enacting an authority set change and
+//! one every `justification_period` blocks. Snapshot one of those. Snapshots without such a proof,
+//! e.g. of chains whose authority set changed since genesis, are only restored with
+//! `--trust-header`.
+//!
+//! A restored database has no block before the snapshot's, which is imported as finalized. The
+//! node carries on from it, but consensus state kept outside the runtime, like the GRANDPA
+//! authority set, is the genesis one: this is meant for dev and test nodes.
+
+use crate::service::FullClient;
+use codec::{Decode, Encode};
+use finality_grandpa::voter_set::VoterSet;
+use node_template_runtime::{opaque::Block, Hash};
+use sc_cli::{BlockNumberOrHash, CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use sc_client_api::{BlockBackend, StorageProvider};
+use sc_consensus::{
+	BlockImport, BlockImportParams, ForkChoiceStrategy, ImportResult, ImportedState, StateAction,
+	StorageChanges,
+};
+use sc_finality_grandpa::GrandpaJustification;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_consensus::BlockOrigin;
+use sp_core::{
+	blake2_256,
+	storage::{well_known_keys, ChildInfo, ChildType, PrefixedStorageKey, StorageKey},
+};
+use sp_finality_grandpa::{AuthorityList, GrandpaApi, SetId, GRANDPA_ENGINE_ID};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT},
+	Justifications,
+};
+use sp_state_machine::{KeyValueStates, KeyValueStorageLevel};
+use std::{collections::HashMap, fs, path::PathBuf, sync::Arc};
+
+/// The first bytes of a snapshot file, with the version of its format.
+const MAGIC: &[u8; 8] = b"NTSNAP01";
+
+/// The zstd compression level of snapshots.
+const COMPRESSION_LEVEL: i32 = 3;
+
+type KeyValues = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// The state of a block with what proves it.
+#[derive(Debug, PartialEq, Eq, Encode, Decode)]
+struct Snapshot {
+	genesis_hash: Hash,
+	header: <Block as BlockT>::Header,
+	justifications: Option<Justifications>,
+	/// The top trie, without the roots of the child tries.
+	top: KeyValues,
+	/// The child tries, by prefixed storage key, with their root.
+	children: Vec<(Vec<u8>, Vec<u8>, KeyValues)>,
+}
+
+impl Snapshot {
+	fn write(&self, path: &PathBuf) -> sc_cli::Result<()> {
+		let compressed = zstd::encode_all(&self.encode()[..], COMPRESSION_LEVEL)?;
+		let file = [&MAGIC[..], &blake2_256(&compressed), &compressed].concat();
+		fs::write(path, file)?;
+		Ok(())
+	}
+
+	fn read(path: &PathBuf) -> sc_cli::Result<Self> {
+		let file = fs::read(path)?;
+		let invalid = |reason: &str| {
+			sc_cli::Error::Input(format!("{} is not a valid snapshot: {}", path.display(), reason))
+		};
+
+		let data = file.strip_prefix(&MAGIC[..]).ok_or_else(|| invalid("unknown format"))?;
+		if data.len() < 32 {
+			return Err(invalid("truncated"))
+		}
+		let (checksum, compressed) = data.split_at(32);
+		if checksum != blake2_256(compressed) {
+			return Err(invalid("checksum mismatch"))
+		}
+		let encoded = zstd::decode_all(compressed)?;
+		Snapshot::decode(&mut &encoded[..]).map_err(|e| invalid(&e.to_string()))
+	}
+}
+
+/// Create or restore state snapshots.
+#[derive(Debug, clap::Subcommand)]
+pub enum SnapshotCmd {
+	/// Save the state of a block to a file.
+	Create(CreateCmd),
+	/// Start an empty database from a snapshot.
+	Restore(RestoreCmd),
+}
+
+/// The `snapshot create` command.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct CreateCmd {
+	/// The block to save the state of, by number or hash. Better a finalized one.
+	#[clap(value_name = "BLOCK")]
+	pub block: BlockNumberOrHash,
+
+	/// The snapshot file to write.
+	#[clap(long, short, value_name = "FILE")]
+	pub output: PathBuf,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+/// The `snapshot restore` command.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct RestoreCmd {
+	/// The snapshot file to read.
+	#[clap(value_name = "FILE")]
+	pub file: PathBuf,
+
+	/// Restore the snapshot even if its header is not proven by a GRANDPA justification of the
+	/// genesis authorities.
+	#[clap(long)]
+	pub trust_header: bool,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl CliConfiguration for CreateCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+impl CliConfiguration for RestoreCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+impl CreateCmd {
+	/// Save the state of the block to the output file.
+	pub fn run(&self, client: &FullClient) -> sc_cli::Result<()> {
+		let at = self.block.parse::<Block>()?;
+		let header = client
+			.header(at)?
+			.ok_or_else(|| sc_cli::Error::Input(format!("Unknown block {}", at)))?;
+		let at = BlockId::Hash(header.hash());
+
+		let mut top = Vec::new();
+		let mut children = Vec::new();
+		for (key, value) in client.storage_pairs(&at, &StorageKey(Vec::new()))? {
+			if !well_known_keys::is_child_storage_key(&key.0) {
+				top.push((key.0, value.0));
+				continue
+			}
+			let child_info = match ChildType::from_prefixed_key(PrefixedStorageKey::new_ref(&key.0))
+			{
+				Some((ChildType::ParentKeyId, storage_key)) => ChildInfo::new_default(storage_key),
+				None => return Err(format!("Unknown child trie type at {:?}", key).into()),
+			};
+			let mut pairs = Vec::new();
+			for child_key in client.child_storage_keys(&at, &child_info, &StorageKey(Vec::new()))? {
+				if let Some(value) = client.child_storage(&at, &child_info, &child_key)? {
+					pairs.push((child_key.0, value.0));
+				}
+			}
+			children.push((key.0, value.0, pairs));
+		}
+
+		let snapshot = Snapshot {
+			genesis_hash: client.info().genesis_hash,
+			justifications: client.justifications(&at)?,
+			header,
+			top,
+			children,
+		};
+		snapshot.write(&self.output)?;
+		println!(
+			"Snapshot of block #{} ({}) written to {}",
+			snapshot.header.number(),
+			snapshot.header.hash(),
+			self.output.display()
+		);
+		let justified = snapshot.justifications.iter().any(|j| j.get(GRANDPA_ENGINE_ID).is_some());
+		if !justified {
+			println!("The block has no GRANDPA justification, restore it with --trust-header");
+		}
+		Ok(())
+	}
+}
+
+impl RestoreCmd {
+	/// Import the snapshot's block and state into the empty database of `client`, once its header
+	/// is proven to be finalized.
+	pub async fn run(&self, client: Arc<FullClient>) -> sc_cli::Result<()> {
+		let snapshot = Snapshot::read(&self.file)?;
+		let info = client.info();
+		if snapshot.genesis_hash != info.genesis_hash {
+			return Err(format!(
+				"The snapshot is of the chain with genesis {}, not {}",
+				snapshot.genesis_hash, info.genesis_hash
+			)
+			.into())
+		}
+		if info.best_number != 0 {
+			return Err("Snapshots are restored to an empty database, purge the chain first".into())
+		}
+
+		let authorities = client
+			.runtime_api()
+			.grandpa_authorities(&BlockId::Hash(info.genesis_hash))
+			.map_err(|e| format!("Unable to read the genesis GRANDPA authorities: {}", e))?;
+		match verify_justification(
+			&snapshot.header,
+			snapshot.justifications.as_ref(),
+			0,
+			&authorities,
+		) {
+			Ok(()) => {},
+			Err(reason) if self.trust_header =>
+				log::warn!("Restoring a snapshot header proven by nothing: {}", reason),
+			Err(reason) =>
+				return Err(format!(
+					"The snapshot header is not proven to be finalized: {}. Pass --trust-header to \
+					 restore it anyway.",
+					reason
+				)
+				.into()),
+		}
+
+		let hash = snapshot.header.hash();
+		let number = *snapshot.header.number();
+		let mut levels = vec![KeyValueStorageLevel {
+			state_root: Vec::new(),
+			parent_storage_keys: Vec::new(),
+			key_values: snapshot.top,
+		}];
+		levels.extend(snapshot.children.into_iter().map(|(storage_key, root, key_values)| {
+			KeyValueStorageLevel {
+				state_root: root,
+				parent_storage_keys: vec![storage_key],
+				key_values,
+			}
+		}));
+
+		let mut import = BlockImportParams::new(BlockOrigin::NetworkInitialSync, snapshot.header);
+		import.justifications = snapshot.justifications;
+		import.finalized = true;
+		import.fork_choice = Some(ForkChoiceStrategy::Custom(true));
+		import.state_action = StateAction::ApplyChanges(StorageChanges::Import(ImportedState {
+			block: hash,
+			state: KeyValueStates(levels),
+		}));
+
+		// The state is checked against the state root of the header on import.
+		match (&*client).import_block(import, HashMap::new()).await {
+			Ok(ImportResult::Imported(_)) => {
+				println!("Block #{} ({}) restored from {}", number, hash, self.file.display());
+				Ok(())
+			},
+			Ok(result) => Err(format!("Snapshot not imported: {:?}", result).into()),
+			Err(e) => Err(format!("Snapshot not imported: {}", e).into()),
+		}
+	}
+}
+
+/// Check that `justifications` hold a GRANDPA justification of `header` by a supermajority of
+/// `authorities`, the authority set `set_id`.
+fn verify_justification(
+	header: &<Block as BlockT>::Header,
+	justifications: Option<&Justifications>,
+	set_id: SetId,
+	authorities: &AuthorityList,
+) -> Result<(), String> {
+	let encoded = justifications
+		.and_then(|justifications| justifications.get(GRANDPA_ENGINE_ID))
+		.ok_or("it has no GRANDPA justification")?;
+	let voters = VoterSet::new(authorities.iter().cloned()).ok_or("no GRANDPA authorities")?;
+	GrandpaJustification::<Block>::decode_and_verify_finalizes(
+		encoded,
+		(header.hash(), *header.number()),
+		set_id,
+		&voters,
+	)
+	.map(drop)
+	.map_err(|e| format!("invalid GRANDPA justification: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use node_template_runtime::{BlockNumber, Header};
+	use sp_finality_grandpa::{AuthorityId, AuthoritySignature};
+	use sp_keyring::Ed25519Keyring::{self, Alice, Bob, Charlie};
+
+	fn snapshot() -> Snapshot {
+		Snapshot {
+			genesis_hash: Hash::repeat_byte(1),
+			header: Header::new(
+				7,
+				Default::default(),
+				Hash::repeat_byte(2),
+				Hash::repeat_byte(3),
+				Default::default(),
+			),
+			justifications: None,
+			top: vec![(b"key".to_vec(), b"value".to_vec())],
+			children: vec![(b":child_storage:default:trie".to_vec(), vec![4; 32], vec![])],
+		}
+	}
+
+	#[test]
+	fn snapshot_files_round_trip() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("snapshot");
+
+		snapshot().write(&path).unwrap();
+		assert_eq!(Snapshot::read(&path).unwrap(), snapshot());
+	}
+
+	#[test]
+	fn corrupted_snapshots_are_rejected() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("snapshot");
+		snapshot().write(&path).unwrap();
+
+		let mut file = fs::read(&path).unwrap();
+		let last = file.len() - 1;
+		file[last] ^= 1;
+		fs::write(&path, &file).unwrap();
+		assert!(Snapshot::read(&path).unwrap_err().to_string().contains("checksum mismatch"));
+
+		fs::write(&path, b"not a snapshot").unwrap();
+		assert!(Snapshot::read(&path).unwrap_err().to_string().contains("unknown format"));
+	}
+
+	/// A justification of `header` in round 1 of the set `set_id`, precommitted by `signers`.
+	fn justification(header: &Header, set_id: SetId, signers: &[Ed25519Keyring]) -> Justifications {
+		const ROUND: u64 = 1;
+		let precommit = finality_grandpa::Precommit {
+			target_hash: header.hash(),
+			target_number: *header.number(),
+		};
+		let message = finality_grandpa::Message::Precommit(precommit.clone());
+		let payload = sp_finality_grandpa::localized_payload(ROUND, set_id, &message);
+		let commit: finality_grandpa::Commit<Hash, BlockNumber, AuthoritySignature, AuthorityId> =
+			finality_grandpa::Commit {
+				target_hash: header.hash(),
+				target_number: *header.number(),
+				precommits: signers
+					.iter()
+					.map(|signer| finality_grandpa::SignedPrecommit {
+						precommit: precommit.clone(),
+						signature: signer.sign(&payload).into(),
+						id: signer.public().into(),
+					})
+					.collect(),
+			};
+		Justifications::from((GRANDPA_ENGINE_ID, (ROUND, commit, Vec::<Header>::new()).encode()))
+	}
+
+	#[test]
+	fn headers_are_proven_by_a_supermajority_of_the_authorities() {
+		let header = snapshot().header;
+		let authorities: AuthorityList =
+			[Alice, Bob, Charlie].iter().map(|key| (key.public().into(), 1)).collect();
+		let verify = |justifications: Option<Justifications>, set_id| {
+			verify_justification(&header, justifications.as_ref(), set_id, &authorities)
+		};
+
+		assert_eq!(verify(Some(justification(&header, 0, &[Alice, Bob, Charlie])), 0), Ok(()));
+		assert!(verify(None, 0).unwrap_err().contains("no GRANDPA justification"));
+		assert!(verify(Some(justification(&header, 0, &[Alice, Bob])), 0).is_err());
+		assert!(verify(Some(justification(&header, 1, &[Alice, Bob, Charlie])), 0).is_err());
+
+		let mut other = header.clone();
+		other.number += 1;
+		assert!(verify(Some(justification(&other, 0, &[Alice, Bob, Charlie])), 0).is_err());
+	}
+}
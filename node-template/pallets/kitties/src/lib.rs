@@ -12,18 +12,26 @@ pub use pallet::*;
 pub mod pallet {
 	use frame_support::{
 		pallet_prelude::*,
-		traits::{tokens::ExistenceRequirement, Currency, Randomness},
+		traits::{
+			tokens::ExistenceRequirement, Currency, OnUnbalanced, Randomness, WithdrawReasons,
+		},
 	};
 	use frame_system::pallet_prelude::*;
 	use scale_info::TypeInfo;
 	use sp_io::hashing::blake2_128;
-	use sp_runtime::ArithmeticError;
+	use sp_runtime::{
+		traits::{Saturating, Zero},
+		ArithmeticError, Permill,
+	};
 
 	#[cfg(feature = "std")]
 	use frame_support::serde::{Deserialize, Serialize};
 
 	type BalanceOf<T> =
 		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+		<T as frame_system::Config>::AccountId,
+	>>::NegativeImbalance;
 
 	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
 	#[scale_info(skip_type_params(T))]
@@ -56,6 +64,12 @@ pub mod pallet {
 		type MaxKittiesOwned: Get<u32>;
 
 		type KittyRandomness: Randomness<Self::Hash, Self::BlockNumber>;
+
+		/// Who can change the marketplace parameters.
+		type MarketplaceOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Handler for the fees taken on kitty sales.
+		type OnMarketplaceFee: OnUnbalanced<NegativeImbalanceOf<Self>>;
 	}
 
 	// Errors
@@ -79,15 +93,18 @@ pub mod pallet {
 		PriceSet { kitty: [u8; 16], price: Option<BalanceOf<T>> },
 		Transferred { from: T::AccountId, to: T::AccountId, kitty: [u8; 16] },
 		Sold { seller: T::AccountId, buyer: T::AccountId, kitty: [u8; 16], price: BalanceOf<T> },
+		MarketplaceFeeSet { fee: Permill },
 	}
 
 	#[pallet::storage]
 	pub(super) type CountForKitties<T: Config> = StorageValue<_, u64, ValueQuery>;
 
 	#[pallet::storage]
+	#[pallet::getter(fn kitty)]
 	pub(super) type Kitties<T: Config> = StorageMap<_, Twox64Concat, [u8; 16], Kitty<T>>;
 
 	#[pallet::storage]
+	#[pallet::getter(fn kitties_owned)]
 	pub(super) type KittiesOwned<T: Config> = StorageMap<
 		_,
 		Twox64Concat,
@@ -96,6 +113,10 @@ pub mod pallet {
 		ValueQuery,
 	>;
 
+	/// The share of every sale price the buyer pays to the marketplace instead of the seller.
+	#[pallet::storage]
+	pub(super) type MarketplaceFee<T: Config> = StorageValue<_, Permill, ValueQuery>;
+
 	#[pallet::genesis_config]
 	pub struct GenesisConfig<T: Config> {
 		pub kitties: Vec<(T::AccountId, [u8; 16], Gender)>,
@@ -195,6 +216,17 @@ pub mod pallet {
 
 			Ok(())
 		}
+
+		#[pallet::weight(0)]
+		pub fn set_marketplace_fee(origin: OriginFor<T>, fee: Permill) -> DispatchResult {
+			T::MarketplaceOrigin::ensure_origin(origin)?;
+
+			MarketplaceFee::<T>::put(fee);
+
+			Self::deposit_event(Event::MarketplaceFeeSet { fee });
+
+			Ok(())
+		}
 	}
 
 	impl<T: Config> Pallet<T> {
@@ -285,7 +317,22 @@ pub mod pallet {
 			if let Some(limit_price) = maybe_limit_price {
 				if let Some(price) = kitty.price {
 					ensure!(limit_price >= price, Error::<T>::BidPriceTooLow);
-					T::Currency::transfer(&to, &from, price, ExistenceRequirement::KeepAlive)?;
+					let fee = MarketplaceFee::<T>::get().mul_floor(price);
+					T::Currency::transfer(
+						&to,
+						&from,
+						price.saturating_sub(fee),
+						ExistenceRequirement::KeepAlive,
+					)?;
+					if !fee.is_zero() {
+						let imbalance = T::Currency::withdraw(
+							&to,
+							fee,
+							WithdrawReasons::TRANSFER,
+							ExistenceRequirement::KeepAlive,
+						)?;
+						T::OnMarketplaceFee::on_unbalanced(imbalance);
+					}
 					Self::deposit_event(Event::Sold {
 						seller: from.clone(),
 						buyer: to.clone(),
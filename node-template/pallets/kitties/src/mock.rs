@@ -84,6 +84,8 @@ impl pallet_kitties::Config for Test {
 	type Currency = Balances;
 	type MaxKittiesOwned = MaxKittiesOwned;
 	type KittyRandomness = RandomnessCollectiveFlip;
+	type MarketplaceOrigin = frame_system::EnsureRoot<u64>;
+	type OnMarketplaceFee = ();
 }
 
 impl pallet_randomness_collective_flip::Config for Test {}
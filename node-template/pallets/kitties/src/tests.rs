@@ -2,6 +2,7 @@
 
 use crate::{mock::*, pallet::Error, *};
 use frame_support::{assert_noop, assert_ok};
+use sp_runtime::Permill;
 
 fn assert_ownership(owner: u64, kitty_id: [u8; 16]) {
 	let kitty = Kitties::<Test>::get(kitty_id).unwrap();
@@ -301,3 +302,39 @@ fn set_price_works() {
 		);
 	});
 }
+
+#[test]
+fn set_marketplace_fee_works() {
+	new_test_ext(vec![]).execute_with(|| {
+		assert_noop!(
+			SubstrateKitties::set_marketplace_fee(Origin::signed(1), Permill::from_percent(5)),
+			sp_runtime::DispatchError::BadOrigin
+		);
+
+		assert_ok!(SubstrateKitties::set_marketplace_fee(Origin::root(), Permill::from_percent(5)));
+		assert_eq!(MarketplaceFee::<Test>::get(), Permill::from_percent(5));
+	});
+}
+
+#[test]
+fn buy_kitty_pays_marketplace_fee() {
+	new_test_ext(vec![
+		(1, *b"1234567890123456", Gender::Female),
+		(2, *b"123456789012345a", Gender::Male),
+	])
+	.execute_with(|| {
+		let id = KittiesOwned::<Test>::get(2)[0];
+		let issuance_before = Balances::total_issuance();
+		let fee = Permill::from_percent(50);
+		assert_ok!(SubstrateKitties::set_marketplace_fee(Origin::root(), fee));
+		assert_ok!(SubstrateKitties::set_price(Origin::signed(2), id, Some(4)));
+
+		assert_ok!(SubstrateKitties::buy_kitty(Origin::signed(1), id, 4));
+
+		assert_eq!(Balances::free_balance(&1), 10 - 4);
+		assert_eq!(Balances::free_balance(&2), 10 + 2);
+		// The mock burns the fee.
+		assert_eq!(Balances::total_issuance(), issuance_before - 2);
+		assert_ownership(1, id);
+	});
+}
@@ -0,0 +1,25 @@
+//! Benchmarking setup for pallet-maintenance
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as Maintenance;
+use frame_benchmarking::benchmarks;
+use frame_system::RawOrigin;
+
+benchmarks! {
+	enter_maintenance {
+	}: _(RawOrigin::Root)
+	verify {
+		assert!(MaintenanceMode::<T>::get());
+	}
+
+	exit_maintenance {
+		MaintenanceMode::<T>::put(true);
+	}: _(RawOrigin::Root)
+	verify {
+		assert!(!MaintenanceMode::<T>::get());
+	}
+
+	impl_benchmark_test_suite!(Maintenance, crate::mock::new_test_ext(), crate::mock::Test);
+}
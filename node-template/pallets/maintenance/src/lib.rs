@@ -0,0 +1,94 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! A switch that puts the runtime into maintenance mode, e.g. while running migrations.
+//!
+//! The pallet is meant to be used as the runtime's `BaseCallFilter`: outside of maintenance mode
+//! it lets every call through, while maintenance mode is on only the calls accepted by
+//! [`Config::MaintenanceCallFilter`] are dispatchable. Root is never filtered, so governance can
+//! always leave maintenance mode again.
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod weights;
+pub use weights::WeightInfo;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::WeightInfo;
+	use frame_support::{pallet_prelude::*, traits::Contains};
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Event: From<Event> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The calls that stay dispatchable while in maintenance mode.
+		type MaintenanceCallFilter: Contains<<Self as frame_system::Config>::Call>;
+
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// Whether the runtime is in maintenance mode.
+	#[pallet::storage]
+	#[pallet::getter(fn maintenance_mode)]
+	pub type MaintenanceMode<T> = StorageValue<_, bool, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event {
+		MaintenanceEntered,
+		MaintenanceExited,
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		AlreadyInMaintenance,
+		NotInMaintenance,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		#[pallet::weight(T::WeightInfo::enter_maintenance())]
+		pub fn enter_maintenance(origin: OriginFor<T>) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(!MaintenanceMode::<T>::get(), Error::<T>::AlreadyInMaintenance);
+
+			MaintenanceMode::<T>::put(true);
+
+			Self::deposit_event(Event::MaintenanceEntered);
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::exit_maintenance())]
+		pub fn exit_maintenance(origin: OriginFor<T>) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(MaintenanceMode::<T>::get(), Error::<T>::NotInMaintenance);
+
+			MaintenanceMode::<T>::put(false);
+
+			Self::deposit_event(Event::MaintenanceExited);
+
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Contains<<T as frame_system::Config>::Call> for Pallet<T> {
+		fn contains(call: &<T as frame_system::Config>::Call) -> bool {
+			!MaintenanceMode::<T>::get() || T::MaintenanceCallFilter::contains(call)
+		}
+	}
+}
@@ -0,0 +1,78 @@
+use crate::{mock::*, Error, Event as MaintenanceEvent};
+use frame_support::{assert_noop, assert_ok, dispatch::Dispatchable, traits::Contains};
+use sp_runtime::DispatchError;
+
+fn remark() -> Call {
+	Call::System(frame_system::Call::remark { remark: vec![1] })
+}
+
+fn remark_with_event() -> Call {
+	Call::System(frame_system::Call::remark_with_event { remark: vec![1] })
+}
+
+#[test]
+fn enter_and_exit_maintenance_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Maintenance::enter_maintenance(Origin::root()));
+		assert!(Maintenance::maintenance_mode());
+		System::assert_last_event(MaintenanceEvent::MaintenanceEntered.into());
+
+		assert_ok!(Maintenance::exit_maintenance(Origin::root()));
+		assert!(!Maintenance::maintenance_mode());
+		System::assert_last_event(MaintenanceEvent::MaintenanceExited.into());
+	});
+}
+
+#[test]
+fn maintenance_calls_require_root() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Maintenance::enter_maintenance(Origin::signed(1)), DispatchError::BadOrigin);
+
+		assert_ok!(Maintenance::enter_maintenance(Origin::root()));
+		assert_noop!(Maintenance::exit_maintenance(Origin::signed(1)), DispatchError::BadOrigin);
+	});
+}
+
+#[test]
+fn maintenance_calls_fail_in_wrong_mode() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Maintenance::exit_maintenance(Origin::root()),
+			Error::<Test>::NotInMaintenance
+		);
+
+		assert_ok!(Maintenance::enter_maintenance(Origin::root()));
+		assert_noop!(
+			Maintenance::enter_maintenance(Origin::root()),
+			Error::<Test>::AlreadyInMaintenance
+		);
+	});
+}
+
+#[test]
+fn filter_only_applies_in_maintenance() {
+	new_test_ext().execute_with(|| {
+		assert!(Maintenance::contains(&remark()));
+		assert!(Maintenance::contains(&remark_with_event()));
+
+		assert_ok!(Maintenance::enter_maintenance(Origin::root()));
+		assert!(!Maintenance::contains(&remark()));
+		assert!(Maintenance::contains(&remark_with_event()));
+
+		assert_ok!(Maintenance::exit_maintenance(Origin::root()));
+		assert!(Maintenance::contains(&remark()));
+	});
+}
+
+#[test]
+fn filtered_calls_cannot_be_dispatched() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Maintenance::enter_maintenance(Origin::root()));
+
+		let err = remark().dispatch(Origin::signed(1)).unwrap_err();
+		assert_eq!(err.error, frame_system::Error::<Test>::CallFiltered.into());
+		assert_ok!(remark_with_event().dispatch(Origin::signed(1)));
+		// Root bypasses the filter, so maintenance mode can always be left.
+		assert_ok!(remark().dispatch(Origin::root()));
+	});
+}
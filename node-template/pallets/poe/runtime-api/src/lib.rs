@@ -0,0 +1,15 @@
+//! Runtime API definition for the proof of existence pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use poe_merkle::Hash;
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+	pub trait PoeApi {
+		/// Check that `leaf` belongs to the batch claim notarized under `root`.
+		///
+		/// Returns `false` if `root` was never claimed or the proof does not match.
+		fn verify_inclusion(root: Hash, leaf: Hash, proof: Vec<Hash>) -> bool;
+	}
+}
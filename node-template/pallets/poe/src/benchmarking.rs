@@ -1,11 +1,24 @@
 use crate::*;
-use frame_benchmarking::{benchmarks, whitelisted_caller, account};
+use frame_benchmarking::{benchmarks, whitelisted_caller, account, BenchmarkError};
+use frame_support::traits::Currency;
 use frame_system::RawOrigin;
+use sp_runtime::traits::Bounded;
+
+#[allow(unused)]
+use crate::Pallet as PoeModule;
 
 fn assert_last_event<T: Config>(generic_event: <T as Config>::Event) {
 	frame_system::Pallet::<T>::assert_last_event(generic_event.into());
 }
 
+// Fill the provenance history of `claim` so that the next transfer has to evict an entry.
+fn fill_history<T: Config>(claim: &BoundedClaim<T>) {
+	let history: Vec<_> = (0..T::MaxClaimHistory::get())
+		.map(|i| (account::<T::AccountId>("previous", i, 0), T::BlockNumber::from(i)))
+		.collect();
+	ClaimHistory::<T>::insert(claim, ClaimHistoryOf::<T>::try_from(history).unwrap());
+}
+
 benchmarks! {
 	create_claim {
 		let d in 0 .. T::ClaimLimitSize::get();
@@ -13,7 +26,7 @@ benchmarks! {
 		let caller: T::AccountId = whitelisted_caller();
 	}: _(RawOrigin::Signed(caller.clone()), claim.clone())
 	verify {
-        let bounded_claim = BoundedClaim::<T>::try_from(claim.clone()).unwrap();
+		let bounded_claim = BoundedClaim::<T>::try_from(claim.clone()).unwrap();
 		assert_last_event::<T>(Event::ClaimCreated(caller, bounded_claim).into())
 	}
 
@@ -21,20 +34,141 @@ benchmarks! {
 		let d in 0 .. T::ClaimLimitSize::get();
 		let claim = vec![0; d as usize];
 		let caller: T::AccountId = whitelisted_caller();
+		let target: T::AccountId = account("target", 0, 0);
 		assert!(Pallet::<T>::create_claim(RawOrigin::Signed(caller.clone()).into(), claim.clone()).is_ok());
+		assert!(Pallet::<T>::offer_claim_transfer(RawOrigin::Signed(caller.clone()).into(), target, claim.clone()).is_ok());
+		let bounded_claim = BoundedClaim::<T>::try_from(claim.clone()).unwrap();
+		fill_history::<T>(&bounded_claim);
 	}: _(RawOrigin::Signed(caller.clone()), claim.clone())
 	verify {
-        let bounded_claim = BoundedClaim::<T>::try_from(claim.clone()).unwrap();
+		assert!(!Proofs::<T>::contains_key(&bounded_claim));
 		assert_last_event::<T>(Event::ClaimRevoked(caller, bounded_claim).into())
 	}
 
-	transfer_claim {
+	create_batch_claim {
+		let root = [0u8; 32];
+		let caller: T::AccountId = whitelisted_caller();
+	}: _(RawOrigin::Signed(caller.clone()), root)
+	verify {
+		assert_last_event::<T>(Event::BatchClaimCreated(caller, root).into())
+	}
+
+	offer_claim_transfer {
+		let d in 0 .. T::ClaimLimitSize::get();
+		let claim = vec![0; d as usize];
+		let caller: T::AccountId = whitelisted_caller();
+		let target: T::AccountId = account("target", 0, 0);
+		assert!(Pallet::<T>::create_claim(RawOrigin::Signed(caller.clone()).into(), claim.clone()).is_ok());
+	}: _(RawOrigin::Signed(caller.clone()), target.clone(), claim.clone())
+	verify {
+		let bounded_claim = BoundedClaim::<T>::try_from(claim.clone()).unwrap();
+		assert_last_event::<T>(Event::ClaimTransferOffered(caller, target, bounded_claim).into())
+	}
+
+	accept_claim_transfer {
+		let d in 0 .. T::ClaimLimitSize::get();
+		let claim = vec![0; d as usize];
+		let owner: T::AccountId = account("owner", 0, 0);
+		let caller: T::AccountId = whitelisted_caller();
+		assert!(Pallet::<T>::create_claim(RawOrigin::Signed(owner.clone()).into(), claim.clone()).is_ok());
+		assert!(Pallet::<T>::offer_claim_transfer(RawOrigin::Signed(owner.clone()).into(), caller.clone(), claim.clone()).is_ok());
+		let bounded_claim = BoundedClaim::<T>::try_from(claim.clone()).unwrap();
+		fill_history::<T>(&bounded_claim);
+	}: _(RawOrigin::Signed(caller.clone()), claim.clone())
+	verify {
+		assert!(!PendingTransfers::<T>::contains_key(&bounded_claim));
+		assert_last_event::<T>(Event::ClaimTransferred(owner, caller, bounded_claim).into())
+	}
+
+	cancel_claim_transfer {
 		let d in 0 .. T::ClaimLimitSize::get();
 		let claim = vec![0; d as usize];
 		let caller: T::AccountId = whitelisted_caller();
 		let target: T::AccountId = account("target", 0, 0);
 		assert!(Pallet::<T>::create_claim(RawOrigin::Signed(caller.clone()).into(), claim.clone()).is_ok());
-	}: _(RawOrigin::Signed(caller), target, claim)
+		assert!(Pallet::<T>::offer_claim_transfer(RawOrigin::Signed(caller.clone()).into(), target, claim.clone()).is_ok());
+	}: _(RawOrigin::Signed(caller.clone()), claim.clone())
+	verify {
+		let bounded_claim = BoundedClaim::<T>::try_from(claim.clone()).unwrap();
+		assert_last_event::<T>(Event::ClaimTransferCancelled(caller, bounded_claim).into())
+	}
+
+	// Failure path: the claim is rejected for being too long before any storage is touched.
+	reject_oversized_claim {
+		let claim = vec![0; T::ClaimLimitSize::get() as usize + 1];
+		let caller: T::AccountId = whitelisted_caller();
+	}: {
+		assert!(Pallet::<T>::create_claim(RawOrigin::Signed(caller.clone()).into(), claim.clone()).is_err());
+	}
+
+	// Failure path: the claim is read but the caller does not own it.
+	reject_claim_call {
+		let d in 0 .. T::ClaimLimitSize::get();
+		let claim = vec![0; d as usize];
+		let owner: T::AccountId = account("owner", 0, 0);
+		let caller: T::AccountId = whitelisted_caller();
+		assert!(Pallet::<T>::create_claim(RawOrigin::Signed(owner.clone()).into(), claim.clone()).is_ok());
+	}: {
+		assert!(Pallet::<T>::revoke_claim(RawOrigin::Signed(caller.clone()).into(), claim.clone()).is_err());
+	}
+	verify {
+		let bounded_claim = BoundedClaim::<T>::try_from(claim.clone()).unwrap();
+		assert_eq!(Proofs::<T>::get(&bounded_claim).map(|(owner, _)| owner), Some(owner));
+	}
+
+	submit_anchor_receipt {
+		let d in 0 .. T::ClaimLimitSize::get();
+		let r in 0 .. T::MaxReceiptLen::get();
+		let claim = vec![0; d as usize];
+		let receipt = vec![1; r as usize];
+		let owner: T::AccountId = account("owner", 0, 0);
+		assert!(Pallet::<T>::create_claim(RawOrigin::Signed(owner).into(), claim.clone()).is_ok());
+		// `EnsureSignedBy` falls back to an account outside the set when the set is empty, e.g.
+		// a runtime whose anchor authorities are the council of a chain spec without one.
+		let origin = T::AnchorOrigin::successful_origin();
+		let submitter = T::AnchorOrigin::ensure_origin(origin.clone())
+			.map_err(|_| BenchmarkError::Stop("AnchorOrigin has no account to benchmark with"))?;
+	}: _<T::Origin>(origin, claim.clone(), receipt.clone())
+	verify {
+		let bounded_claim = BoundedClaim::<T>::try_from(claim.clone()).unwrap();
+		let bounded_receipt = BoundedReceipt::<T>::try_from(receipt.clone()).unwrap();
+		assert!(!PendingAnchors::<T>::contains_key(&bounded_claim));
+		assert_last_event::<T>(Event::ClaimAnchored(submitter, bounded_claim, bounded_receipt).into())
+	}
+
+	dispute_claim {
+		let d in 0 .. T::ClaimLimitSize::get();
+		let r in 0 .. T::MaxReasonLen::get();
+		let claim = vec![0; d as usize];
+		let reason = vec![1; r as usize];
+		let owner: T::AccountId = account("owner", 0, 0);
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value());
+		assert!(Pallet::<T>::create_claim(RawOrigin::Signed(owner).into(), claim.clone()).is_ok());
+	}: _(RawOrigin::Signed(caller.clone()), claim.clone(), reason)
+	verify {
+		let bounded_claim = BoundedClaim::<T>::try_from(claim.clone()).unwrap();
+		assert_eq!(Pallet::<T>::claim_status(&bounded_claim), Some(ClaimStatus::Disputed));
+		assert_last_event::<T>(Event::ClaimDisputed(caller, bounded_claim).into())
+	}
+
+	// Revoking the claim is the most expensive resolution.
+	resolve_dispute {
+		let d in 0 .. T::ClaimLimitSize::get();
+		let claim = vec![0; d as usize];
+		let owner: T::AccountId = account("owner", 0, 0);
+		let challenger: T::AccountId = account("challenger", 0, 0);
+		T::Currency::make_free_balance_be(&challenger, BalanceOf::<T>::max_value());
+		assert!(Pallet::<T>::create_claim(RawOrigin::Signed(owner.clone()).into(), claim.clone()).is_ok());
+		let reason = vec![1; T::MaxReasonLen::get() as usize];
+		assert!(Pallet::<T>::dispute_claim(RawOrigin::Signed(challenger).into(), claim.clone(), reason).is_ok());
+		let origin = T::AdjudicatorOrigin::successful_origin();
+	}: _<T::Origin>(origin, claim.clone(), DisputeResolution::Revoke)
+	verify {
+		let bounded_claim = BoundedClaim::<T>::try_from(claim.clone()).unwrap();
+		assert!(!Proofs::<T>::contains_key(&bounded_claim));
+		assert_last_event::<T>(Event::ClaimRevokedByAuthority(owner, bounded_claim).into())
+	}
 
 	impl_benchmark_test_suite!(PoeModule, crate::mock::new_test_ext(), crate::mock::Test);
 }
@@ -7,8 +7,10 @@ mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+pub mod offchain;
 pub mod weights;
 
+pub use offchain::{crypto, ENDPOINT_KEY, KEY_TYPE};
 pub use pallet::*;
 pub use weights::WeightInfo;
 
@@ -18,15 +20,90 @@ pub mod pallet {
 	pub use frame_system::pallet_prelude::*;
 	pub use sp_std::prelude::*;
 	use super::WeightInfo;
+	use frame_support::{
+		dispatch::DispatchErrorWithPostInfo,
+		traits::{Currency, OnUnbalanced, ReservableCurrency},
+		weights::WithPostDispatchInfo,
+	};
+	use frame_system::offchain::{AppCrypto, CreateSignedTransaction};
 
 	pub type BoundedClaim<T> = BoundedVec<u8, <T as Config>::ClaimLimitSize>;
+	pub type BoundedReceipt<T> = BoundedVec<u8, <T as Config>::MaxReceiptLen>;
+	pub type MerkleRoot = poe_merkle::Hash;
+	pub type ClaimHistoryOf<T> = BoundedVec<
+		(<T as frame_system::Config>::AccountId, <T as frame_system::Config>::BlockNumber),
+		<T as Config>::MaxClaimHistory,
+	>;
+	pub type BoundedReason<T> = BoundedVec<u8, <T as Config>::MaxReasonLen>;
+	pub type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+	pub type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+		<T as frame_system::Config>::AccountId,
+	>>::NegativeImbalance;
+
+	/// Whether a claim can currently be moved or revoked by its owner.
+	#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub enum ClaimStatus {
+		Active,
+		/// A dispute is open; the claim stays put until the adjudicator resolves it.
+		Disputed,
+	}
+
+	/// How the adjudicator settles a dispute.
+	#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub enum DisputeResolution {
+		/// The claim stands and the challenger gets the bond back.
+		Uphold,
+		/// The claim is revoked and the challenger gets the bond back.
+		Revoke,
+		/// The claim stands and the challenger's bond is slashed.
+		Slash,
+	}
+
+	/// An open dispute over a claim.
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	#[scale_info(skip_type_params(T))]
+	pub struct Dispute<T: Config> {
+		pub challenger: T::AccountId,
+		pub bond: BalanceOf<T>,
+		pub reason: BoundedReason<T>,
+		pub opened: T::BlockNumber,
+	}
 
 	#[pallet::config]
-	pub trait Config: frame_system::Config {
+	pub trait Config: CreateSignedTransaction<Call<Self>> + frame_system::Config {
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
 		/// The maximum length of claim that can be added.
 		#[pallet::constant]
 		type ClaimLimitSize: Get<u32>;
+		/// The maximum number of prior owners remembered per claim. Older entries are dropped
+		/// first.
+		#[pallet::constant]
+		type MaxClaimHistory: Get<u32>;
+
+		/// The identifier type for the keys signing anchor receipts.
+		type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+		/// Who may submit anchor receipts; the account is recorded alongside the receipt.
+		type AnchorOrigin: EnsureOrigin<Self::Origin, Success = Self::AccountId>;
+		/// The maximum length of a receipt returned by the timestamp log.
+		#[pallet::constant]
+		type MaxReceiptLen: Get<u32>;
+		/// The maximum number of claims the off-chain worker anchors per block.
+		#[pallet::constant]
+		type MaxAnchorsPerBlock: Get<u32>;
+
+		/// The currency dispute bonds are reserved in.
+		type Currency: ReservableCurrency<Self::AccountId>;
+		/// The amount reserved from whoever disputes a claim.
+		#[pallet::constant]
+		type DisputeBond: Get<BalanceOf<Self>>;
+		/// The maximum length of the reason given for a dispute.
+		#[pallet::constant]
+		type MaxReasonLen: Get<u32>;
+		/// Who resolves disputes.
+		type AdjudicatorOrigin: EnsureOrigin<Self::Origin>;
+		/// Handler for slashed dispute bonds.
+		type Slashed: OnUnbalanced<NegativeImbalanceOf<Self>>;
 
 		type WeightInfo: WeightInfo;
 	}
@@ -40,12 +117,90 @@ pub mod pallet {
 	pub type Proofs<T: Config> =
 		StorageMap<_, Blake2_128Concat, BoundedClaim<T>, (T::AccountId, T::BlockNumber), OptionQuery>;
 
+	/// Transfers offered by the claim owner and waiting for the recipient to accept them.
+	#[pallet::storage]
+	#[pallet::getter(fn pending_transfers)]
+	pub type PendingTransfers<T: Config> =
+		StorageMap<_, Blake2_128Concat, BoundedClaim<T>, T::AccountId, OptionQuery>;
+
+	/// Prior owners of a claim together with the block at which they handed it over, oldest
+	/// first.
+	#[pallet::storage]
+	#[pallet::getter(fn claim_history)]
+	pub type ClaimHistory<T: Config> =
+		StorageMap<_, Blake2_128Concat, BoundedClaim<T>, ClaimHistoryOf<T>, ValueQuery>;
+
+	/// Claims waiting to be mirrored to the external timestamp log, with the block they were
+	/// created at.
+	#[pallet::storage]
+	#[pallet::getter(fn pending_anchors)]
+	pub type PendingAnchors<T: Config> =
+		StorageMap<_, Blake2_128Concat, BoundedClaim<T>, T::BlockNumber, OptionQuery>;
+
+	/// Receipts returned by the external timestamp log, with the account that submitted them.
+	#[pallet::storage]
+	#[pallet::getter(fn anchor_receipts)]
+	pub type AnchorReceipts<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		BoundedClaim<T>,
+		(T::AccountId, BoundedReceipt<T>),
+		OptionQuery,
+	>;
+
+	/// Open disputes, at most one per claim.
+	#[pallet::storage]
+	#[pallet::getter(fn disputes)]
+	pub type Disputes<T: Config> =
+		StorageMap<_, Blake2_128Concat, BoundedClaim<T>, Dispute<T>, OptionQuery>;
+
+	/// Merkle roots of notarized document batches.
+	#[pallet::storage]
+	#[pallet::getter(fn batch_proofs)]
+	pub type BatchProofs<T: Config> =
+		StorageMap<_, Blake2_128Concat, MerkleRoot, (T::AccountId, T::BlockNumber), OptionQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Claims notarized at genesis, with their owner. They aren't anchored to the timestamp
+		/// log.
+		pub claims: Vec<(T::AccountId, Vec<u8>)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> GenesisConfig<T> {
+			GenesisConfig { claims: vec![] }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for (owner, claim) in &self.claims {
+				let claim = BoundedClaim::<T>::try_from(claim.clone())
+					.expect("genesis claims must fit in ClaimLimitSize");
+				assert!(!Proofs::<T>::contains_key(&claim), "duplicate genesis claim");
+				Proofs::<T>::insert(&claim, (owner.clone(), T::BlockNumber::default()));
+			}
+		}
+	}
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
 		ClaimCreated(T::AccountId, BoundedClaim<T>),
 		ClaimRevoked(T::AccountId, BoundedClaim<T>),
 		ClaimTransferred(T::AccountId, T::AccountId, BoundedClaim<T>),
+		BatchClaimCreated(T::AccountId, MerkleRoot),
+		ClaimTransferOffered(T::AccountId, T::AccountId, BoundedClaim<T>),
+		ClaimTransferCancelled(T::AccountId, BoundedClaim<T>),
+		ClaimAnchored(T::AccountId, BoundedClaim<T>, BoundedReceipt<T>),
+		ClaimDisputed(T::AccountId, BoundedClaim<T>),
+		/// The dispute was dismissed and the bond returned to the challenger.
+		ClaimUpheld(T::AccountId, BoundedClaim<T>),
+		ClaimRevokedByAuthority(T::AccountId, BoundedClaim<T>),
+		DisputeBondSlashed(T::AccountId, BoundedClaim<T>, BalanceOf<T>),
 	}
 
 	#[pallet::error]
@@ -54,61 +209,363 @@ pub mod pallet {
 		ClaimTooLong,
 		ClaimNotExists,
 		NotClaimOwner,
+		BatchAlreadyExists,
+		TransferToSelf,
+		NoPendingTransfer,
+		NotTransferRecipient,
+		ClaimNotPending,
+		ReceiptTooLong,
+		ClaimDisputed,
+		AlreadyDisputed,
+		CannotDisputeOwnClaim,
+		ReasonTooLong,
+		NoOpenDispute,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn offchain_worker(now: T::BlockNumber) {
+			if let Err(e) = Self::anchor_pending_claims(now) {
+				log::warn!(target: "runtime::poe", "anchoring claims skipped: {}", e);
+			}
+		}
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		#[pallet::weight(T::WeightInfo::create_claim(claim.len() as u32))]
+		#[pallet::weight(T::WeightInfo::create_claim(claim_weight_len::<T>(&claim)))]
 		pub fn create_claim(origin: OriginFor<T>, claim: Vec<u8>) -> DispatchResultWithPostInfo {
 			let sender = ensure_signed(origin)?;
 
-			let bounded_claim = BoundedClaim::<T>::try_from(claim.clone()).map_err(|_| Error::<T>::ClaimTooLong)?;
+			let bounded_claim = Self::bound_claim(claim)?;
 			ensure!(!Proofs::<T>::contains_key(&bounded_claim), Error::<T>::ProofAlreadyExists);
 
-			Proofs::<T>::insert(
-				&bounded_claim,
-				(sender.clone(), frame_system::Pallet::<T>::block_number()),
-			);
+			let now = frame_system::Pallet::<T>::block_number();
+			Proofs::<T>::insert(&bounded_claim, (sender.clone(), now));
+			PendingAnchors::<T>::insert(&bounded_claim, now);
 
 			Self::deposit_event(Event::ClaimCreated(sender, bounded_claim));
 
 			Ok(().into())
 		}
 
-		#[pallet::weight(T::WeightInfo::revoke_claim(claim.len() as u32))]
+		#[pallet::weight(T::WeightInfo::revoke_claim(claim_weight_len::<T>(&claim)))]
 		pub fn revoke_claim(origin: OriginFor<T>, claim: Vec<u8>) -> DispatchResultWithPostInfo {
 			let sender = ensure_signed(origin)?;
 
-			let bounded_claim = BoundedClaim::<T>::try_from(claim.clone()).map_err(|_| Error::<T>::ClaimTooLong)?;
-			let (owner, _) = Proofs::<T>::get(&bounded_claim).ok_or(Error::<T>::ClaimNotExists)?;
-
-			ensure!(owner == sender, Error::<T>::NotClaimOwner);
+			let bounded_claim = Self::bound_claim(claim)?;
+			Self::ensure_claim_owner(&bounded_claim, &sender)?;
+			Self::ensure_not_disputed(&bounded_claim)?;
 
-			Proofs::<T>::remove(&bounded_claim);
+			Self::remove_claim(&bounded_claim);
 
 			Self::deposit_event(Event::ClaimRevoked(sender, bounded_claim));
 
 			Ok(().into())
 		}
 
-		#[pallet::weight(T::WeightInfo::transfer_claim(claim.len() as u32))]
-		pub fn transfer_claim(origin: OriginFor<T>, to: T::AccountId, claim: Vec<u8>) -> DispatchResultWithPostInfo {
+		/// Kept for existing callers: the same as `offer_claim_transfer`, the claim only changes
+		/// hands once `to` accepts it.
+		#[pallet::weight(T::WeightInfo::offer_claim_transfer(claim_weight_len::<T>(&claim)))]
+		pub fn transfer_claim(
+			origin: OriginFor<T>,
+			to: T::AccountId,
+			claim: Vec<u8>,
+		) -> DispatchResultWithPostInfo {
+			Self::offer_claim_transfer(origin, to, claim)
+		}
+
+		/// Offer the claim to `to`, who becomes the owner once they accept it. A new offer
+		/// replaces any previous one.
+		#[pallet::weight(T::WeightInfo::offer_claim_transfer(claim_weight_len::<T>(&claim)))]
+		pub fn offer_claim_transfer(
+			origin: OriginFor<T>,
+			to: T::AccountId,
+			claim: Vec<u8>,
+		) -> DispatchResultWithPostInfo {
 			let sender = ensure_signed(origin)?;
 
-			let bounded_claim = BoundedClaim::<T>::try_from(claim.clone()).map_err(|_| Error::<T>::ClaimTooLong)?;
-			let (owner, _) = Proofs::<T>::get(&bounded_claim).ok_or(Error::<T>::ClaimNotExists)?;
+			let bounded_claim = Self::bound_claim(claim)?;
+			Self::ensure_claim_owner(&bounded_claim, &sender)?;
+			Self::ensure_not_disputed(&bounded_claim)?;
+			ensure!(to != sender, Error::<T>::TransferToSelf);
+
+			PendingTransfers::<T>::insert(&bounded_claim, &to);
+
+			Self::deposit_event(Event::ClaimTransferOffered(sender, to, bounded_claim));
+
+			Ok(().into())
+		}
+
+		/// Accept a transfer offered to the caller and take ownership of the claim.
+		#[pallet::weight(T::WeightInfo::accept_claim_transfer(claim_weight_len::<T>(&claim)))]
+		pub fn accept_claim_transfer(
+			origin: OriginFor<T>,
+			claim: Vec<u8>,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+
+			let bounded_claim = Self::bound_claim(claim)?;
+			let owner = Self::claim_owner(&bounded_claim)?;
+			let recipient =
+				PendingTransfers::<T>::get(&bounded_claim).ok_or(Error::<T>::NoPendingTransfer)?;
+
+			ensure!(recipient == sender, Error::<T>::NotTransferRecipient);
+			Self::ensure_not_disputed(&bounded_claim)?;
+
+			Self::do_transfer(&bounded_claim, owner, sender);
+
+			Ok(().into())
+		}
+
+		/// Withdraw a pending transfer offer. Only the claim owner can do this.
+		#[pallet::weight(T::WeightInfo::cancel_claim_transfer(claim_weight_len::<T>(&claim)))]
+		pub fn cancel_claim_transfer(
+			origin: OriginFor<T>,
+			claim: Vec<u8>,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+
+			let bounded_claim = Self::bound_claim(claim)?;
+			Self::ensure_claim_owner(&bounded_claim, &sender)?;
+			ensure!(
+				PendingTransfers::<T>::contains_key(&bounded_claim),
+				Error::<T>::NoPendingTransfer
+			);
 
-			ensure!(owner == sender, Error::<T>::NotClaimOwner);
+			PendingTransfers::<T>::remove(&bounded_claim);
 
-			Proofs::<T>::mutate(&bounded_claim, |opt| {
+			Self::deposit_event(Event::ClaimTransferCancelled(sender, bounded_claim));
+
+			Ok(().into())
+		}
+
+		/// Notarize a whole batch of documents at once by claiming the Merkle root built over
+		/// their digests with [`poe_merkle::merkle_root`].
+		#[pallet::weight(T::WeightInfo::create_batch_claim())]
+		pub fn create_batch_claim(
+			origin: OriginFor<T>,
+			root: MerkleRoot,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(!BatchProofs::<T>::contains_key(&root), Error::<T>::BatchAlreadyExists);
+
+			BatchProofs::<T>::insert(
+				&root,
+				(sender.clone(), frame_system::Pallet::<T>::block_number()),
+			);
+
+			Self::deposit_event(Event::BatchClaimCreated(sender, root));
+
+			Ok(().into())
+		}
+
+		/// Record the receipt the external timestamp log returned for a pending claim. Sent by
+		/// the off-chain worker.
+		#[pallet::weight(T::WeightInfo::submit_anchor_receipt(
+			claim_weight_len::<T>(&claim),
+			(receipt.len() as u32).min(T::MaxReceiptLen::get()),
+		))]
+		pub fn submit_anchor_receipt(
+			origin: OriginFor<T>,
+			claim: Vec<u8>,
+			receipt: Vec<u8>,
+		) -> DispatchResultWithPostInfo {
+			let sender = T::AnchorOrigin::ensure_origin(origin)?;
+
+			let bounded_claim = Self::bound_claim(claim)?;
+			let bounded_receipt =
+				BoundedReceipt::<T>::try_from(receipt).map_err(|_| Error::<T>::ReceiptTooLong)?;
+			let created =
+				PendingAnchors::<T>::take(&bounded_claim).ok_or(Error::<T>::ClaimNotPending)?;
+
+			AnchorReceipts::<T>::insert(&bounded_claim, (sender.clone(), bounded_receipt.clone()));
+			Self::clear_anchor_state(&bounded_claim, created);
+
+			Self::deposit_event(Event::ClaimAnchored(sender, bounded_claim, bounded_receipt));
+
+			Ok(().into())
+		}
+
+		/// Challenge someone else's claim, reserving `DisputeBond` from the caller. The claim can
+		/// neither be transferred nor revoked by its owner until the dispute is resolved.
+		#[pallet::weight(T::WeightInfo::dispute_claim(
+			claim_weight_len::<T>(&claim),
+			(reason.len() as u32).min(T::MaxReasonLen::get()),
+		))]
+		pub fn dispute_claim(
+			origin: OriginFor<T>,
+			claim: Vec<u8>,
+			reason: Vec<u8>,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+
+			let bounded_claim = Self::bound_claim(claim)?;
+			let owner = Self::claim_owner(&bounded_claim)?;
+			ensure!(owner != sender, Error::<T>::CannotDisputeOwnClaim);
+			ensure!(!Disputes::<T>::contains_key(&bounded_claim), Error::<T>::AlreadyDisputed);
+			let reason =
+				BoundedReason::<T>::try_from(reason).map_err(|_| Error::<T>::ReasonTooLong)?;
+
+			let bond = T::DisputeBond::get();
+			T::Currency::reserve(&sender, bond)?;
+			Disputes::<T>::insert(
+				&bounded_claim,
+				Dispute {
+					challenger: sender.clone(),
+					bond,
+					reason,
+					opened: frame_system::Pallet::<T>::block_number(),
+				},
+			);
+
+			Self::deposit_event(Event::ClaimDisputed(sender, bounded_claim));
+
+			Ok(().into())
+		}
+
+		/// Settle the open dispute over `claim`. See [`DisputeResolution`] for the outcomes.
+		#[pallet::weight(T::WeightInfo::resolve_dispute(claim_weight_len::<T>(&claim)))]
+		pub fn resolve_dispute(
+			origin: OriginFor<T>,
+			claim: Vec<u8>,
+			resolution: DisputeResolution,
+		) -> DispatchResultWithPostInfo {
+			T::AdjudicatorOrigin::ensure_origin(origin)?;
+
+			let bounded_claim = Self::bound_claim(claim)?;
+			let dispute = Disputes::<T>::take(&bounded_claim).ok_or(Error::<T>::NoOpenDispute)?;
+
+			match resolution {
+				DisputeResolution::Uphold => {
+					T::Currency::unreserve(&dispute.challenger, dispute.bond);
+					Self::deposit_event(Event::ClaimUpheld(dispute.challenger, bounded_claim));
+				},
+				DisputeResolution::Revoke => {
+					let owner = Self::claim_owner(&bounded_claim)?;
+					T::Currency::unreserve(&dispute.challenger, dispute.bond);
+					Self::remove_claim(&bounded_claim);
+					Self::deposit_event(Event::ClaimRevokedByAuthority(owner, bounded_claim));
+				},
+				DisputeResolution::Slash => {
+					let (imbalance, _) =
+						T::Currency::slash_reserved(&dispute.challenger, dispute.bond);
+					T::Slashed::on_unbalanced(imbalance);
+					Self::deposit_event(Event::DisputeBondSlashed(
+						dispute.challenger,
+						bounded_claim,
+						dispute.bond,
+					));
+				},
+			}
+
+			Ok(().into())
+		}
+	}
+
+	/// The claim length used to weigh a call. Claims longer than `ClaimLimitSize` are rejected
+	/// before touching storage, so they never cost more than the longest accepted claim.
+	fn claim_weight_len<T: Config>(claim: &[u8]) -> u32 {
+		(claim.len() as u32).min(T::ClaimLimitSize::get())
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Bound `claim` to `ClaimLimitSize`, refunding everything but the cost of the check when
+		/// it is too long.
+		fn bound_claim(claim: Vec<u8>) -> Result<BoundedClaim<T>, DispatchErrorWithPostInfo> {
+			BoundedClaim::<T>::try_from(claim).map_err(|_| {
+				Error::<T>::ClaimTooLong.with_weight(T::WeightInfo::reject_oversized_claim())
+			})
+		}
+
+		/// Look up the owner of `claim`, refunding everything but the read when it does not
+		/// exist.
+		fn claim_owner(claim: &BoundedClaim<T>) -> Result<T::AccountId, DispatchErrorWithPostInfo> {
+			Proofs::<T>::get(claim).map(|(owner, _)| owner).ok_or_else(|| {
+				Error::<T>::ClaimNotExists
+					.with_weight(T::WeightInfo::reject_claim_call(claim.len() as u32))
+			})
+		}
+
+		/// Ensure `who` owns `claim`, refunding everything but the read when it does not.
+		fn ensure_claim_owner(
+			claim: &BoundedClaim<T>,
+			who: &T::AccountId,
+		) -> Result<(), DispatchErrorWithPostInfo> {
+			let owner = Self::claim_owner(claim)?;
+			if &owner != who {
+				return Err(Error::<T>::NotClaimOwner
+					.with_weight(T::WeightInfo::reject_claim_call(claim.len() as u32)))
+			}
+			Ok(())
+		}
+
+		/// Refuse to move a claim while it is disputed, refunding everything but the reads of the
+		/// claim and of its dispute.
+		fn ensure_not_disputed(claim: &BoundedClaim<T>) -> Result<(), DispatchErrorWithPostInfo> {
+			if Disputes::<T>::contains_key(claim) {
+				return Err(Error::<T>::ClaimDisputed.with_weight(
+					T::WeightInfo::reject_claim_call(claim.len() as u32)
+						.saturating_add(T::DbWeight::get().reads(1)),
+				))
+			}
+			Ok(())
+		}
+
+		/// The status of `claim`, or `None` if it does not exist.
+		pub fn claim_status(claim: &BoundedClaim<T>) -> Option<ClaimStatus> {
+			if !Proofs::<T>::contains_key(claim) {
+				return None
+			}
+			Some(if Disputes::<T>::contains_key(claim) {
+				ClaimStatus::Disputed
+			} else {
+				ClaimStatus::Active
+			})
+		}
+
+		/// Drop `claim` and everything recorded about it.
+		fn remove_claim(claim: &BoundedClaim<T>) {
+			Proofs::<T>::remove(claim);
+			PendingTransfers::<T>::remove(claim);
+			ClaimHistory::<T>::remove(claim);
+			AnchorReceipts::<T>::remove(claim);
+			if let Some(created) = PendingAnchors::<T>::take(claim) {
+				Self::clear_anchor_state(claim, created);
+			}
+		}
+
+		/// Move `claim` from `from` to `to`, recording `from` in the claim history and dropping
+		/// any pending offer.
+		fn do_transfer(claim: &BoundedClaim<T>, from: T::AccountId, to: T::AccountId) {
+			let now = frame_system::Pallet::<T>::block_number();
+
+			Proofs::<T>::mutate(claim, |opt| {
 				if let Some(tuple) = opt {
 					tuple.0 = to.clone();
 				}
 			});
+			PendingTransfers::<T>::remove(claim);
+			ClaimHistory::<T>::mutate(claim, |history| {
+				if history.len() as u32 >= T::MaxClaimHistory::get() && !history.is_empty() {
+					history.remove(0);
+				}
+				// Only fails when `MaxClaimHistory` is zero, i.e. no history is kept at all.
+				let _ = history.try_push((from.clone(), now));
+			});
 
-			Self::deposit_event(Event::ClaimTransferred(sender, to, bounded_claim));
+			Self::deposit_event(Event::ClaimTransferred(from, to, claim.clone()));
+		}
 
-			Ok(().into())
+		/// Check that the document digest `leaf` is part of a notarized batch with the given
+		/// `root`, using a proof produced by [`poe_merkle::merkle_proof`].
+		pub fn verify_inclusion(
+			root: MerkleRoot,
+			leaf: MerkleRoot,
+			proof: Vec<MerkleRoot>,
+		) -> bool {
+			BatchProofs::<T>::contains_key(&root) && poe_merkle::verify_proof(&root, &leaf, &proof)
 		}
 	}
 }
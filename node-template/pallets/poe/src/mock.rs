@@ -1,10 +1,16 @@
 use crate as pallet_poe;
 use frame_support::traits::{ConstU16, ConstU32, ConstU64};
-use sp_core::H256;
+use frame_system::{EnsureRoot, EnsureSigned};
+use parking_lot::RwLock;
+use sp_core::{
+	offchain::{testing, OffchainDbExt, OffchainWorkerExt, TransactionPoolExt},
+	H256,
+};
 use sp_runtime::{
-	testing::Header,
-	traits::{BlakeTwo256, IdentityLookup},
+	testing::{Header, TestSignature, TestXt, UintAuthorityId},
+	traits::{BlakeTwo256, Extrinsic as ExtrinsicT, IdentityLookup},
 };
+use std::sync::Arc;
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -17,7 +23,8 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
-		Poe: pallet_poe::{Pallet, Call, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Poe: pallet_poe::{Pallet, Call, Storage, Config<T>, Event<T>},
 	}
 );
 
@@ -39,7 +46,7 @@ impl frame_system::Config for Test {
 	type DbWeight = ();
 	type Version = ();
 	type PalletInfo = PalletInfo;
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u64>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
@@ -48,19 +55,103 @@ impl frame_system::Config for Test {
 	type MaxConsumers = ConstU32<16>;
 }
 
+impl pallet_balances::Config for Test {
+	type Balance = u64;
+	type DustRemoval = ();
+	type Event = Event;
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+}
+
+pub type Extrinsic = TestXt<Call, ()>;
+
+impl frame_system::offchain::SigningTypes for Test {
+	type Public = UintAuthorityId;
+	type Signature = TestSignature;
+}
+
+impl<LocalCall> frame_system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+	Call: From<LocalCall>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = Extrinsic;
+}
+
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Test
+where
+	Call: From<LocalCall>,
+{
+	fn create_transaction<C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: Call,
+		_public: UintAuthorityId,
+		_account: u64,
+		nonce: u64,
+	) -> Option<(Call, <Extrinsic as ExtrinsicT>::SignaturePayload)> {
+		Some((call, (nonce, ())))
+	}
+}
+
+/// Signs anchor receipts with the `UintAuthorityId` test keys.
+pub struct TestAuthId;
+
+impl frame_system::offchain::AppCrypto<UintAuthorityId, TestSignature> for TestAuthId {
+	type RuntimeAppPublic = UintAuthorityId;
+	type GenericSignature = TestSignature;
+	type GenericPublic = UintAuthorityId;
+}
+
 impl pallet_poe::Config for Test {
 	type Event = Event;
 	type ClaimLimitSize = ConstU32<5>;
+	type MaxClaimHistory = ConstU32<2>;
+	type AuthorityId = TestAuthId;
+	type AnchorOrigin = EnsureSigned<u64>;
+	type MaxReceiptLen = ConstU32<8>;
+	type MaxAnchorsPerBlock = ConstU32<2>;
+	type Currency = Balances;
+	type DisputeBond = ConstU64<10>;
+	type MaxReasonLen = ConstU32<8>;
+	type AdjudicatorOrigin = EnsureRoot<u64>;
+	type Slashed = ();
 	type WeightInfo = ();
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	let mut ext = sp_io::TestExternalities::new(
-		frame_system::GenesisConfig::default().build_storage::<Test>().unwrap(),
-	);
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: vec![(1, 100), (2, 100), (3, 5)] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(t);
 	ext.execute_with(|| {
 		System::set_block_number(1); //must set block number, otherwise the deposit_event() don't work
 	});
 	ext
 }
+
+/// The account of the test key the off-chain worker signs with.
+pub const ANCHOR_KEY: u64 = 7;
+
+/// Like `new_test_ext`, but with the off-chain worker APIs mocked. The returned states are used
+/// to answer the worker's HTTP requests and to inspect the transactions it submits.
+pub fn new_offchain_test_ext() -> (
+	sp_io::TestExternalities,
+	Arc<RwLock<testing::OffchainState>>,
+	Arc<RwLock<testing::PoolState>>,
+) {
+	let (offchain, offchain_state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	UintAuthorityId::set_all_keys(vec![ANCHOR_KEY]);
+
+	let mut ext = new_test_ext();
+	ext.register_extension(OffchainDbExt::new(offchain.clone()));
+	ext.register_extension(OffchainWorkerExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+	(ext, offchain_state, pool_state)
+}
@@ -0,0 +1,257 @@
+//! Off-chain worker mirroring new claims into an external append-only log.
+//!
+//! Every claim created on chain is queued in [`PendingAnchors`]. On each block the off-chain
+//! worker POSTs a JSON record for the queued claims to the endpoint configured in the node's
+//! persistent local storage under [`ENDPOINT_KEY`] and sends the service's reply back on chain as
+//! a receipt through a signed `submit_anchor_receipt` transaction. Failed requests are retried
+//! with an exponential backoff tracked in local storage, so the chain itself never sees them.
+//!
+//! The signing key is looked up in the node keystore under [`KEY_TYPE`], e.g. inserted with the
+//! `author_insertKey` RPC, and the endpoint can be set with `offchain_localStorageSet`.
+//!
+//! The local progress of a claim is keyed by the claim and the block it was created at, so a claim
+//! revoked and created again starts afresh. Nodes running with `--enable-offchain-indexing` also
+//! drop the progress of claims that are no longer pending.
+
+use crate::{BoundedClaim, Call, Config, Pallet, PendingAnchors, Proofs};
+use codec::{Decode, Encode};
+use frame_support::traits::Get;
+use frame_system::offchain::{SendSignedTransaction, Signer};
+use sp_core::crypto::KeyTypeId;
+use sp_runtime::{
+	offchain::{
+		http,
+		storage::StorageValueRef,
+		storage_lock::{StorageLock, Time},
+		Duration,
+	},
+	traits::{Saturating, UniqueSaturatedInto},
+};
+use sp_std::prelude::*;
+
+/// Key type of the accounts signing anchor receipts.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"poe!");
+
+/// Local storage key holding the URL of the timestamp log, as UTF-8 bytes.
+pub const ENDPOINT_KEY: &[u8] = b"poe::anchor-endpoint";
+
+const LOCK_KEY: &[u8] = b"poe::anchor-lock";
+const STATE_PREFIX: &[u8] = b"poe::anchor::";
+
+/// How long a single HTTP request may take.
+const HTTP_TIMEOUT_MS: u64 = 3_000;
+/// How long the worker holds the lock, so overlapping runs don't anchor the same claim twice.
+const LOCK_TIMEOUT_MS: u64 = 10_000;
+/// Retry delays start at this many blocks and double after every failed attempt...
+const BASE_BACKOFF: u32 = 1;
+/// ...up to this many blocks.
+const MAX_BACKOFF: u32 = 256;
+/// Blocks to wait for a submitted receipt to be included before submitting it again.
+const RECEIPT_TIMEOUT: u32 = 10;
+
+pub mod crypto {
+	use super::KEY_TYPE;
+	use sp_core::sr25519::Signature as Sr25519Signature;
+	use sp_runtime::{
+		app_crypto::{app_crypto, sr25519},
+		traits::Verify,
+		MultiSignature, MultiSigner,
+	};
+
+	app_crypto!(sr25519, KEY_TYPE);
+
+	/// Identifies the keys allowed to sign anchor receipts.
+	pub struct AnchorAuthId;
+
+	impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for AnchorAuthId {
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+
+	impl frame_system::offchain::AppCrypto<<Sr25519Signature as Verify>::Signer, Sr25519Signature>
+		for AnchorAuthId
+	{
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+}
+
+/// Per-claim anchoring progress, kept in the node's local storage.
+#[derive(Encode, Decode, Clone, PartialEq, Default, sp_runtime::RuntimeDebug)]
+pub struct AnchorState<BlockNumber> {
+	/// Failed HTTP attempts so far.
+	pub attempts: u32,
+	/// Nothing is done for the claim before this block.
+	pub next_attempt: BlockNumber,
+	/// The receipt returned by the log, once it accepted the record.
+	pub receipt: Option<Vec<u8>>,
+}
+
+impl<T: Config> Pallet<T> {
+	/// Anchor the claims that are due at block `now`.
+	pub(crate) fn anchor_pending_claims(now: T::BlockNumber) -> Result<(), &'static str> {
+		let endpoint = match StorageValueRef::persistent(ENDPOINT_KEY).get::<Vec<u8>>() {
+			Ok(Some(endpoint)) => endpoint,
+			// Anchoring is disabled on this node.
+			_ => return Ok(()),
+		};
+		let endpoint =
+			sp_std::str::from_utf8(&endpoint).map_err(|_| "anchor endpoint is not UTF-8")?;
+
+		let signer = Signer::<T, T::AuthorityId>::any_account();
+		if !signer.can_sign() {
+			return Err("no local key available to sign anchor receipts")
+		}
+
+		let mut lock = StorageLock::<Time>::with_deadline(
+			LOCK_KEY,
+			Duration::from_millis(LOCK_TIMEOUT_MS),
+		);
+		let _guard = lock.try_lock().map_err(|_| "another anchoring run is in progress")?;
+
+		let due = PendingAnchors::<T>::iter()
+			.filter_map(|(claim, created)| {
+				let state = Self::anchor_state(&claim, created);
+				(state.next_attempt <= now).then(|| (claim, created, state))
+			})
+			.take(T::MaxAnchorsPerBlock::get() as usize)
+			.collect::<Vec<_>>();
+
+		for (claim, created, mut state) in due {
+			let receipt = match state.receipt.take() {
+				Some(receipt) => receipt,
+				None => match Self::post_record(endpoint, &claim) {
+					Ok(receipt) => receipt,
+					Err(e) => {
+						log::warn!(target: "runtime::poe", "anchoring claim failed: {:?}", e);
+						state.attempts = state.attempts.saturating_add(1);
+						state.next_attempt = now.saturating_add(Self::backoff(state.attempts));
+						Self::set_anchor_state(&claim, created, &state);
+						continue
+					},
+				},
+			};
+
+			let result = signer.send_signed_transaction(|_| Call::submit_anchor_receipt {
+				claim: claim.to_vec(),
+				receipt: receipt.clone(),
+			});
+			if !matches!(result, Some((_, Ok(())))) {
+				log::warn!(target: "runtime::poe", "submitting anchor receipt failed");
+			}
+
+			// Whether or not the submission worked, keep the receipt so the next try does not
+			// append the record to the log a second time.
+			state.receipt = Some(receipt);
+			state.next_attempt = now.saturating_add(RECEIPT_TIMEOUT.into());
+			Self::set_anchor_state(&claim, created, &state);
+		}
+
+		Ok(())
+	}
+
+	/// Forget the local anchoring progress of `claim`, created at block `created`, once it is no
+	/// longer pending.
+	///
+	/// Called from on-chain code, so this only takes effect on nodes running with offchain
+	/// indexing enabled. Elsewhere the stale entry is never read again: a claim created again gets
+	/// a key of its own.
+	pub(crate) fn clear_anchor_state(claim: &BoundedClaim<T>, created: T::BlockNumber) {
+		sp_io::offchain_index::clear(&Self::anchor_state_key(claim, created));
+	}
+
+	fn post_record(endpoint: &str, claim: &BoundedClaim<T>) -> Result<Vec<u8>, http::Error> {
+		let (owner, block) = Proofs::<T>::get(claim).ok_or(http::Error::Unknown)?;
+		let body = Self::anchor_record(claim, &owner, block);
+
+		let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(HTTP_TIMEOUT_MS));
+		let pending = http::Request::post(endpoint, vec![body])
+			.add_header("Content-Type", "application/json")
+			.deadline(deadline)
+			.send()
+			.map_err(|_| http::Error::IoError)?;
+		let response = pending.try_wait(deadline).map_err(|_| http::Error::DeadlineReached)??;
+		if !(200..300).contains(&response.code) {
+			log::warn!(target: "runtime::poe", "unexpected anchor status code: {}", response.code);
+			return Err(http::Error::Unknown)
+		}
+
+		Ok(response.body().collect())
+	}
+
+	/// The JSON record appended to the log for `claim`.
+	pub(crate) fn anchor_record(
+		claim: &BoundedClaim<T>,
+		owner: &T::AccountId,
+		block: T::BlockNumber,
+	) -> Vec<u8> {
+		let block: u64 = block.unique_saturated_into();
+		let mut record = b"{\"claim\":\"".to_vec();
+		record.extend(hex(claim));
+		record.extend(b"\",\"owner\":\"");
+		record.extend(hex(&owner.encode()));
+		record.extend(b"\",\"block\":");
+		record.extend(u64_to_ascii(block));
+		record.push(b'}');
+		record
+	}
+
+	/// The delay before retrying after `attempts` failed attempts.
+	pub(crate) fn backoff(attempts: u32) -> T::BlockNumber {
+		let delay = BASE_BACKOFF.saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)));
+		delay.min(MAX_BACKOFF).into()
+	}
+
+	pub(crate) fn anchor_state(
+		claim: &BoundedClaim<T>,
+		created: T::BlockNumber,
+	) -> AnchorState<T::BlockNumber> {
+		StorageValueRef::persistent(&Self::anchor_state_key(claim, created))
+			.get()
+			.ok()
+			.flatten()
+			.unwrap_or_default()
+	}
+
+	pub(crate) fn set_anchor_state(
+		claim: &BoundedClaim<T>,
+		created: T::BlockNumber,
+		state: &AnchorState<T::BlockNumber>,
+	) {
+		StorageValueRef::persistent(&Self::anchor_state_key(claim, created)).set(state);
+	}
+
+	/// The SCALE encoding of the claim is length prefixed, so keys of different claims never
+	/// collide whatever their creation block.
+	fn anchor_state_key(claim: &BoundedClaim<T>, created: T::BlockNumber) -> Vec<u8> {
+		let mut key = STATE_PREFIX.to_vec();
+		(claim, created).encode_to(&mut key);
+		key
+	}
+}
+
+fn hex(bytes: &[u8]) -> Vec<u8> {
+	const DIGITS: &[u8; 16] = b"0123456789abcdef";
+	let mut out = Vec::with_capacity(2 + bytes.len() * 2);
+	out.extend(b"0x");
+	for byte in bytes {
+		out.push(DIGITS[(byte >> 4) as usize]);
+		out.push(DIGITS[(byte & 0xf) as usize]);
+	}
+	out
+}
+
+fn u64_to_ascii(mut n: u64) -> Vec<u8> {
+	let mut digits = Vec::new();
+	loop {
+		digits.push(b'0' + (n % 10) as u8);
+		n /= 10;
+		if n == 0 {
+			break
+		}
+	}
+	digits.reverse();
+	digits
+}
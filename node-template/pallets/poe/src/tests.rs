@@ -1,5 +1,23 @@
-use crate::{mock::*, BoundedClaim, Error, Proofs};
-use frame_support::{assert_noop, assert_ok};
+use crate::{
+	mock::*, offchain::AnchorState, AnchorReceipts, BatchProofs, BoundedClaim, BoundedReceipt,
+	ClaimHistory, ClaimStatus, DisputeResolution, Disputes, Error, PendingAnchors,
+	PendingTransfers, Proofs, WeightInfo, ENDPOINT_KEY,
+};
+use codec::Decode;
+use frame_support::{
+	assert_noop, assert_ok,
+	dispatch::{DispatchResult, DispatchResultWithPostInfo},
+	traits::{GenesisBuild, Get, OffchainWorker},
+};
+use sp_runtime::DispatchError;
+use sp_core::offchain::testing::PendingRequest;
+use sp_runtime::offchain::storage::StorageValueRef;
+
+// Calls refund unused weight when they fail early, so drop the post info before comparing errors
+// with `assert_noop!`.
+fn strip_post_info(result: DispatchResultWithPostInfo) -> DispatchResult {
+	result.map(|_| ()).map_err(|e| e.error)
+}
 
 fn pop_event() -> Event {
 	System::events()
@@ -8,6 +26,22 @@ fn pop_event() -> Event {
 		.event
 }
 
+#[test]
+fn should_build_genesis_claims() {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	crate::GenesisConfig::<Test> { claims: vec![(1, vec![1, 2, 3]), (2, vec![4, 5])] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+
+	sp_io::TestExternalities::new(t).execute_with(|| {
+		let claim = BoundedClaim::<Test>::try_from(vec![1, 2, 3]).unwrap();
+		assert_eq!(Proofs::<Test>::get(&claim), Some((1, 0)));
+		let claim = BoundedClaim::<Test>::try_from(vec![4, 5]).unwrap();
+		assert_eq!(Proofs::<Test>::get(&claim), Some((2, 0)));
+		assert_eq!(PendingAnchors::<Test>::iter().count(), 0);
+	});
+}
+
 #[test]
 fn create_claim_works() {
 	new_test_ext().execute_with(|| {
@@ -40,7 +74,7 @@ fn create_claim_failed_when_claim_already_exist() {
 fn create_claim_failed_when_exceed_claim_size() {
 	new_test_ext().execute_with(|| {
 		assert_noop!(
-			Poe::create_claim(Origin::signed(1), vec![1, 2, 3, 4, 5, 6]),
+			strip_post_info(Poe::create_claim(Origin::signed(1), vec![1, 2, 3, 4, 5, 6])),
 			Error::<Test>::ClaimTooLong
 		);
 	});
@@ -63,7 +97,7 @@ fn revoke_claim_works() {
 fn revoke_claim_failed_when_claim_not_exists() {
 	new_test_ext().execute_with(|| {
 		assert_noop!(
-			Poe::revoke_claim(Origin::signed(1), vec![1, 3, 9]),
+			strip_post_info(Poe::revoke_claim(Origin::signed(1), vec![1, 3, 9])),
 			Error::<Test>::ClaimNotExists
 		);
 	});
@@ -73,7 +107,7 @@ fn revoke_claim_failed_when_claim_not_exists() {
 fn revoke_claim_failed_when_exceed_claim_size() {
 	new_test_ext().execute_with(|| {
 		assert_noop!(
-			Poe::revoke_claim(Origin::signed(1), vec![1, 2, 3, 4, 5, 6]),
+			strip_post_info(Poe::revoke_claim(Origin::signed(1), vec![1, 2, 3, 4, 5, 6])),
 			Error::<Test>::ClaimTooLong
 		);
 	});
@@ -85,25 +119,28 @@ fn revoke_claim_failed_when_not_claim_owner() {
 		let claim = vec![1, 2, 3, 4, 5];
 		assert_ok!(Poe::create_claim(Origin::signed(1), claim.clone()));
 		assert_noop!(
-			Poe::revoke_claim(Origin::signed(2), claim.clone()),
+			strip_post_info(Poe::revoke_claim(Origin::signed(2), claim.clone())),
 			Error::<Test>::NotClaimOwner
 		);
 	});
 }
 
 #[test]
-fn transfer_claim_works() {
+fn transfer_claim_only_offers() {
 	new_test_ext().execute_with(|| {
 		let claim = vec![1, 2, 3, 4, 5];
 		assert_ok!(Poe::create_claim(Origin::signed(1), claim.clone()));
 		assert_ok!(Poe::transfer_claim(Origin::signed(1), 2, claim.clone()));
 		let bounded_claim = BoundedClaim::<Test>::try_from(claim.clone()).unwrap();
+		assert_eq!(Proofs::<Test>::get(&bounded_claim), Some((1, 1)));
+		assert_eq!(PendingTransfers::<Test>::get(&bounded_claim), Some(2));
 		assert_eq!(
-			Proofs::<Test>::get(&bounded_claim),
-			Some((2, frame_system::Pallet::<Test>::block_number()))
+			Event::from(super::Event::ClaimTransferOffered(1, 2, bounded_claim.clone())),
+			pop_event()
 		);
 
-		assert_eq!(Event::from(super::Event::ClaimTransferred(1, 2, bounded_claim)), pop_event());
+		assert_ok!(Poe::accept_claim_transfer(Origin::signed(2), claim));
+		assert_eq!(Proofs::<Test>::get(&bounded_claim), Some((2, 1)));
 	});
 }
 
@@ -111,7 +148,7 @@ fn transfer_claim_works() {
 fn transfer_claim_failed_when_claim_not_exists() {
 	new_test_ext().execute_with(|| {
 		assert_noop!(
-			Poe::transfer_claim(Origin::signed(1), 2, vec![1, 3, 9]),
+			strip_post_info(Poe::transfer_claim(Origin::signed(1), 2, vec![1, 3, 9])),
 			Error::<Test>::ClaimNotExists
 		);
 	});
@@ -121,7 +158,7 @@ fn transfer_claim_failed_when_claim_not_exists() {
 fn transfer_claim_failed_when_exceed_claim_size() {
 	new_test_ext().execute_with(|| {
 		assert_noop!(
-			Poe::transfer_claim(Origin::signed(1), 2, vec![1, 2, 3, 4, 5, 6]),
+			strip_post_info(Poe::transfer_claim(Origin::signed(1), 2, vec![1, 2, 3, 4, 5, 6])),
 			Error::<Test>::ClaimTooLong
 		);
 	});
@@ -133,8 +170,702 @@ fn transfer_claim_failed_when_not_claim_owner() {
 		let claim = vec![1, 2, 3, 4, 5];
 		assert_ok!(Poe::create_claim(Origin::signed(1), claim.clone()));
 		assert_noop!(
-			Poe::transfer_claim(Origin::signed(2), 1, claim.clone()),
+			strip_post_info(Poe::transfer_claim(Origin::signed(2), 1, claim.clone())),
+			Error::<Test>::NotClaimOwner
+		);
+	});
+}
+
+#[test]
+fn transfer_claim_failed_when_transfer_to_self() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![1, 2, 3, 4, 5];
+		assert_ok!(Poe::create_claim(Origin::signed(1), claim.clone()));
+		assert_noop!(
+			Poe::transfer_claim(Origin::signed(1), 1, claim.clone()),
+			Error::<Test>::TransferToSelf
+		);
+	});
+}
+
+#[test]
+fn transfer_claim_records_history() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![1, 2, 3, 4, 5];
+		let bounded_claim = BoundedClaim::<Test>::try_from(claim.clone()).unwrap();
+		assert_ok!(Poe::create_claim(Origin::signed(1), claim.clone()));
+
+		assert_ok!(Poe::offer_claim_transfer(Origin::signed(1), 2, claim.clone()));
+		assert_ok!(Poe::accept_claim_transfer(Origin::signed(2), claim.clone()));
+		System::set_block_number(2);
+		assert_ok!(Poe::offer_claim_transfer(Origin::signed(2), 3, claim.clone()));
+		assert_ok!(Poe::accept_claim_transfer(Origin::signed(3), claim.clone()));
+		assert_eq!(ClaimHistory::<Test>::get(&bounded_claim).into_inner(), vec![(1, 1), (2, 2)]);
+
+		// `MaxClaimHistory` is 2 in the mock, so the oldest owner is dropped.
+		System::set_block_number(3);
+		assert_ok!(Poe::offer_claim_transfer(Origin::signed(3), 4, claim.clone()));
+		assert_ok!(Poe::accept_claim_transfer(Origin::signed(4), claim.clone()));
+		assert_eq!(ClaimHistory::<Test>::get(&bounded_claim).into_inner(), vec![(2, 2), (3, 3)]);
+	});
+}
+
+#[test]
+fn offer_and_accept_claim_transfer_works() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![1, 2, 3, 4, 5];
+		let bounded_claim = BoundedClaim::<Test>::try_from(claim.clone()).unwrap();
+		assert_ok!(Poe::create_claim(Origin::signed(1), claim.clone()));
+
+		assert_ok!(Poe::offer_claim_transfer(Origin::signed(1), 2, claim.clone()));
+		assert_eq!(PendingTransfers::<Test>::get(&bounded_claim), Some(2));
+		assert_eq!(
+			Event::from(super::Event::ClaimTransferOffered(1, 2, bounded_claim.clone())),
+			pop_event()
+		);
+		// Ownership does not move until the recipient accepts.
+		assert_eq!(Proofs::<Test>::get(&bounded_claim), Some((1, 1)));
+
+		assert_ok!(Poe::accept_claim_transfer(Origin::signed(2), claim.clone()));
+		assert_eq!(Proofs::<Test>::get(&bounded_claim), Some((2, 1)));
+		assert_eq!(PendingTransfers::<Test>::get(&bounded_claim), None);
+		assert_eq!(ClaimHistory::<Test>::get(&bounded_claim).into_inner(), vec![(1, 1)]);
+		assert_eq!(
+			Event::from(super::Event::ClaimTransferred(1, 2, bounded_claim)),
+			pop_event()
+		);
+	});
+}
+
+#[test]
+fn offer_claim_transfer_failed_when_invalid() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![1, 2, 3, 4, 5];
+		assert_noop!(
+			strip_post_info(Poe::offer_claim_transfer(
+				Origin::signed(1),
+				2,
+				vec![1, 2, 3, 4, 5, 6]
+			)),
+			Error::<Test>::ClaimTooLong
+		);
+		assert_noop!(
+			strip_post_info(Poe::offer_claim_transfer(Origin::signed(1), 2, claim.clone())),
+			Error::<Test>::ClaimNotExists
+		);
+
+		assert_ok!(Poe::create_claim(Origin::signed(1), claim.clone()));
+		assert_noop!(
+			strip_post_info(Poe::offer_claim_transfer(Origin::signed(2), 3, claim.clone())),
 			Error::<Test>::NotClaimOwner
 		);
+		assert_noop!(
+			Poe::offer_claim_transfer(Origin::signed(1), 1, claim.clone()),
+			Error::<Test>::TransferToSelf
+		);
+	});
+}
+
+#[test]
+fn accept_claim_transfer_failed_when_not_recipient() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![1, 2, 3, 4, 5];
+		assert_ok!(Poe::create_claim(Origin::signed(1), claim.clone()));
+		assert_noop!(
+			Poe::accept_claim_transfer(Origin::signed(2), claim.clone()),
+			Error::<Test>::NoPendingTransfer
+		);
+
+		assert_ok!(Poe::offer_claim_transfer(Origin::signed(1), 2, claim.clone()));
+		assert_noop!(
+			Poe::accept_claim_transfer(Origin::signed(3), claim.clone()),
+			Error::<Test>::NotTransferRecipient
+		);
+	});
+}
+
+#[test]
+fn cancel_claim_transfer_works() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![1, 2, 3, 4, 5];
+		let bounded_claim = BoundedClaim::<Test>::try_from(claim.clone()).unwrap();
+		assert_ok!(Poe::create_claim(Origin::signed(1), claim.clone()));
+		assert_noop!(
+			Poe::cancel_claim_transfer(Origin::signed(1), claim.clone()),
+			Error::<Test>::NoPendingTransfer
+		);
+
+		assert_ok!(Poe::offer_claim_transfer(Origin::signed(1), 2, claim.clone()));
+		assert_noop!(
+			strip_post_info(Poe::cancel_claim_transfer(Origin::signed(2), claim.clone())),
+			Error::<Test>::NotClaimOwner
+		);
+
+		assert_ok!(Poe::cancel_claim_transfer(Origin::signed(1), claim.clone()));
+		assert_eq!(PendingTransfers::<Test>::get(&bounded_claim), None);
+		assert_eq!(
+			Event::from(super::Event::ClaimTransferCancelled(1, bounded_claim)),
+			pop_event()
+		);
+		assert_noop!(
+			Poe::accept_claim_transfer(Origin::signed(2), claim.clone()),
+			Error::<Test>::NoPendingTransfer
+		);
+	});
+}
+
+#[test]
+fn revoke_claim_clears_pending_transfer_and_history() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![1, 2, 3, 4, 5];
+		let bounded_claim = BoundedClaim::<Test>::try_from(claim.clone()).unwrap();
+		assert_ok!(Poe::create_claim(Origin::signed(1), claim.clone()));
+		assert_ok!(Poe::offer_claim_transfer(Origin::signed(1), 2, claim.clone()));
+		assert_ok!(Poe::accept_claim_transfer(Origin::signed(2), claim.clone()));
+		assert_ok!(Poe::offer_claim_transfer(Origin::signed(2), 3, claim.clone()));
+
+		assert_ok!(Poe::revoke_claim(Origin::signed(2), claim.clone()));
+		assert_eq!(PendingTransfers::<Test>::get(&bounded_claim), None);
+		assert!(ClaimHistory::<Test>::get(&bounded_claim).is_empty());
+	});
+}
+
+#[test]
+fn create_batch_claim_works() {
+	new_test_ext().execute_with(|| {
+		let leaves = vec![[1u8; 32], [2u8; 32], [3u8; 32]];
+		let root = poe_merkle::merkle_root(&leaves).unwrap();
+		assert_ok!(Poe::create_batch_claim(Origin::signed(1), root));
+
+		assert_eq!(
+			BatchProofs::<Test>::get(&root),
+			Some((1, frame_system::Pallet::<Test>::block_number()))
+		);
+
+		assert_eq!(Event::from(super::Event::BatchClaimCreated(1, root)), pop_event());
+	});
+}
+
+#[test]
+fn create_batch_claim_failed_when_batch_already_exist() {
+	new_test_ext().execute_with(|| {
+		let root = poe_merkle::merkle_root(&[[1u8; 32]]).unwrap();
+		assert_ok!(Poe::create_batch_claim(Origin::signed(1), root));
+		assert_noop!(
+			Poe::create_batch_claim(Origin::signed(2), root),
+			Error::<Test>::BatchAlreadyExists
+		);
+	});
+}
+
+#[test]
+fn verify_inclusion_works() {
+	new_test_ext().execute_with(|| {
+		let leaves = vec![[1u8; 32], [2u8; 32], [3u8; 32]];
+		let root = poe_merkle::merkle_root(&leaves).unwrap();
+		let proof = poe_merkle::merkle_proof(&leaves, 1).unwrap();
+
+		// Not claimed yet, so nothing can be proven against it.
+		assert!(!Poe::verify_inclusion(root, leaves[1], proof.clone()));
+
+		assert_ok!(Poe::create_batch_claim(Origin::signed(1), root));
+		assert!(Poe::verify_inclusion(root, leaves[1], proof.clone()));
+		assert!(!Poe::verify_inclusion(root, leaves[2], proof));
+		assert!(!Poe::verify_inclusion(root, [9u8; 32], vec![]));
+	});
+}
+
+#[test]
+fn oversized_claim_refunds_weight() {
+	new_test_ext().execute_with(|| {
+		let err = Poe::transfer_claim(Origin::signed(1), 2, vec![1, 2, 3, 4, 5, 6]).unwrap_err();
+		assert_eq!(err.error, Error::<Test>::ClaimTooLong.into());
+		assert_eq!(err.post_info.actual_weight, Some(<() as WeightInfo>::reject_oversized_claim()));
+	});
+}
+
+#[test]
+fn missing_or_foreign_claim_refunds_weight() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![1, 2, 3];
+		let refunded = Some(<() as WeightInfo>::reject_claim_call(claim.len() as u32));
+
+		let err = Poe::revoke_claim(Origin::signed(1), claim.clone()).unwrap_err();
+		assert_eq!(err.error, Error::<Test>::ClaimNotExists.into());
+		assert_eq!(err.post_info.actual_weight, refunded);
+
+		assert_ok!(Poe::create_claim(Origin::signed(1), claim.clone()));
+		let err = Poe::offer_claim_transfer(Origin::signed(2), 3, claim.clone()).unwrap_err();
+		assert_eq!(err.error, Error::<Test>::NotClaimOwner.into());
+		assert_eq!(err.post_info.actual_weight, refunded);
+	});
+}
+
+#[test]
+fn oversized_claim_is_weighed_as_longest_accepted_claim() {
+	use frame_support::weights::GetDispatchInfo;
+
+	let call = crate::Call::<Test>::create_claim { claim: vec![0; 1024] };
+	assert_eq!(call.get_dispatch_info().weight, <() as WeightInfo>::create_claim(5));
+}
+
+// The off-chain worker tests run against the `sp_core::offchain::testing` HTTP mock, which
+// checks every request the worker sends and replays a canned response. Talking to a real local
+// server would take the node's `sc-offchain` HTTP client and a full client around it, which a
+// pallet crate can't depend on; the requests themselves are plain `sp_runtime::offchain::http`.
+const ENDPOINT: &str = "http://localhost:8080/anchor";
+
+fn set_endpoint() {
+	StorageValueRef::persistent(ENDPOINT_KEY).set(&ENDPOINT.as_bytes().to_vec());
+}
+
+fn expect_anchor_request(
+	state: &parking_lot::RwLock<sp_core::offchain::testing::OffchainState>,
+	body: &[u8],
+	receipt: &[u8],
+) {
+	state.write().expect_request(PendingRequest {
+		method: "POST".into(),
+		uri: ENDPOINT.into(),
+		headers: vec![("Content-Type".into(), "application/json".into())],
+		body: body.to_vec(),
+		response: Some(receipt.to_vec()),
+		sent: true,
+		..Default::default()
+	});
+}
+
+fn submitted_calls(pool: &parking_lot::RwLock<sp_core::offchain::testing::PoolState>) -> Vec<Call> {
+	pool.write()
+		.transactions
+		.drain(..)
+		.map(|tx| {
+			let tx = Extrinsic::decode(&mut &*tx).unwrap();
+			assert_eq!(tx.signature.map(|(nonce, _)| nonce), Some(0));
+			tx.call
+		})
+		.collect()
+}
+
+#[test]
+fn create_claim_queues_anchor_and_revoke_drops_it() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![1, 2, 3];
+		let bounded_claim = BoundedClaim::<Test>::try_from(claim.clone()).unwrap();
+
+		assert_ok!(Poe::create_claim(Origin::signed(1), claim.clone()));
+		assert_eq!(PendingAnchors::<Test>::get(&bounded_claim), Some(1));
+
+		assert_ok!(Poe::revoke_claim(Origin::signed(1), claim));
+		assert!(!PendingAnchors::<Test>::contains_key(&bounded_claim));
+	});
+}
+
+#[test]
+fn offchain_worker_anchors_new_claims() {
+	let (mut ext, offchain_state, pool_state) = new_offchain_test_ext();
+	ext.execute_with(|| {
+		set_endpoint();
+		assert_ok!(Poe::create_claim(Origin::signed(1), vec![1, 2, 3]));
+		expect_anchor_request(
+			&offchain_state,
+			br#"{"claim":"0x010203","owner":"0x0100000000000000","block":1}"#,
+			b"rcpt-1",
+		);
+
+		Poe::offchain_worker(1);
+
+		assert_eq!(
+			submitted_calls(&pool_state),
+			vec![Call::Poe(crate::Call::submit_anchor_receipt {
+				claim: vec![1, 2, 3],
+				receipt: b"rcpt-1".to_vec(),
+			})]
+		);
+	});
+}
+
+#[test]
+fn offchain_worker_does_nothing_without_endpoint() {
+	let (mut ext, _, pool_state) = new_offchain_test_ext();
+	ext.execute_with(|| {
+		assert_ok!(Poe::create_claim(Origin::signed(1), vec![1, 2, 3]));
+
+		// Any HTTP request would panic, as none is expected.
+		Poe::offchain_worker(1);
+
+		assert!(pool_state.read().transactions.is_empty());
+	});
+}
+
+#[test]
+fn offchain_worker_resubmits_cached_receipt_without_posting_again() {
+	let (mut ext, offchain_state, pool_state) = new_offchain_test_ext();
+	ext.execute_with(|| {
+		set_endpoint();
+		assert_ok!(Poe::create_claim(Origin::signed(1), vec![1, 2, 3]));
+		expect_anchor_request(
+			&offchain_state,
+			br#"{"claim":"0x010203","owner":"0x0100000000000000","block":1}"#,
+			b"rcpt-1",
+		);
+		Poe::offchain_worker(1);
+		assert_eq!(submitted_calls(&pool_state).len(), 1);
+
+		// The receipt was not included yet: wait before submitting it again.
+		Poe::offchain_worker(2);
+		assert!(submitted_calls(&pool_state).is_empty());
+
+		Poe::offchain_worker(11);
+		assert_eq!(
+			submitted_calls(&pool_state),
+			vec![Call::Poe(crate::Call::submit_anchor_receipt {
+				claim: vec![1, 2, 3],
+				receipt: b"rcpt-1".to_vec(),
+			})]
+		);
+	});
+}
+
+#[test]
+fn offchain_worker_waits_for_backoff_before_retrying() {
+	let (mut ext, offchain_state, pool_state) = new_offchain_test_ext();
+	ext.execute_with(|| {
+		set_endpoint();
+		assert_ok!(Poe::create_claim(Origin::signed(1), vec![1, 2, 3]));
+		let bounded_claim = BoundedClaim::<Test>::try_from(vec![1, 2, 3]).unwrap();
+		Poe::set_anchor_state(
+			&bounded_claim,
+			1,
+			&AnchorState { attempts: 3, next_attempt: 5, receipt: None },
+		);
+
+		Poe::offchain_worker(4);
+		assert!(pool_state.read().transactions.is_empty());
+
+		expect_anchor_request(
+			&offchain_state,
+			br#"{"claim":"0x010203","owner":"0x0100000000000000","block":1}"#,
+			b"rcpt-1",
+		);
+		Poe::offchain_worker(5);
+		assert_eq!(submitted_calls(&pool_state).len(), 1);
+	});
+}
+
+#[test]
+fn offchain_worker_limits_anchors_per_block() {
+	let (mut ext, offchain_state, pool_state) = new_offchain_test_ext();
+	ext.execute_with(|| {
+		set_endpoint();
+		for claim in [vec![1], vec![2], vec![3]] {
+			assert_ok!(Poe::create_claim(Origin::signed(1), claim));
+		}
+		let mut anchored = Vec::new();
+		for claim in PendingAnchors::<Test>::iter_keys().take(2) {
+			let body = format!(
+				r#"{{"claim":"0x{:02x}","owner":"0x0100000000000000","block":1}}"#,
+				claim[0]
+			);
+			expect_anchor_request(&offchain_state, body.as_bytes(), b"rcpt");
+			anchored.push(claim);
+		}
+
+		Poe::offchain_worker(1);
+
+		assert_eq!(submitted_calls(&pool_state).len(), 2);
+		assert!(anchored.iter().all(|claim| Poe::anchor_state(claim, 1).receipt.is_some()));
+	});
+}
+
+#[test]
+fn offchain_worker_anchors_recreated_claim_afresh() {
+	let (mut ext, offchain_state, pool_state) = new_offchain_test_ext();
+	ext.execute_with(|| {
+		set_endpoint();
+		assert_ok!(Poe::create_claim(Origin::signed(1), vec![1, 2, 3]));
+		expect_anchor_request(
+			&offchain_state,
+			br#"{"claim":"0x010203","owner":"0x0100000000000000","block":1}"#,
+			b"rcpt-1",
+		);
+		Poe::offchain_worker(1);
+		assert_eq!(submitted_calls(&pool_state).len(), 1);
+
+		// The cached receipt of the revoked claim must not be reused for the new one.
+		System::set_block_number(2);
+		assert_ok!(Poe::revoke_claim(Origin::signed(1), vec![1, 2, 3]));
+		assert_ok!(Poe::create_claim(Origin::signed(2), vec![1, 2, 3]));
+		expect_anchor_request(
+			&offchain_state,
+			br#"{"claim":"0x010203","owner":"0x0200000000000000","block":2}"#,
+			b"rcpt-2",
+		);
+		Poe::offchain_worker(2);
+		assert_eq!(
+			submitted_calls(&pool_state),
+			vec![Call::Poe(crate::Call::submit_anchor_receipt {
+				claim: vec![1, 2, 3],
+				receipt: b"rcpt-2".to_vec(),
+			})]
+		);
+	});
+}
+
+#[test]
+fn backoff_doubles_up_to_the_limit() {
+	assert_eq!(Poe::backoff(1), 1);
+	assert_eq!(Poe::backoff(2), 2);
+	assert_eq!(Poe::backoff(5), 16);
+	assert_eq!(Poe::backoff(9), 256);
+	assert_eq!(Poe::backoff(40), 256);
+}
+
+#[test]
+fn submit_anchor_receipt_works() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![1, 2, 3];
+		let bounded_claim = BoundedClaim::<Test>::try_from(claim.clone()).unwrap();
+		assert_ok!(Poe::create_claim(Origin::signed(1), claim.clone()));
+
+		assert_ok!(Poe::submit_anchor_receipt(Origin::signed(ANCHOR_KEY), claim, b"rcpt".to_vec()));
+
+		let receipt = BoundedReceipt::<Test>::try_from(b"rcpt".to_vec()).unwrap();
+		assert!(!PendingAnchors::<Test>::contains_key(&bounded_claim));
+		assert_eq!(
+			AnchorReceipts::<Test>::get(&bounded_claim),
+			Some((ANCHOR_KEY, receipt.clone()))
+		);
+		assert_eq!(
+			Event::from(super::Event::ClaimAnchored(ANCHOR_KEY, bounded_claim, receipt)),
+			pop_event()
+		);
+	});
+}
+
+#[test]
+fn submit_anchor_receipt_failed_when_not_pending() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![1, 2, 3];
+		assert_noop!(
+			strip_post_info(Poe::submit_anchor_receipt(
+				Origin::signed(ANCHOR_KEY),
+				claim.clone(),
+				b"rcpt".to_vec()
+			)),
+			Error::<Test>::ClaimNotPending
+		);
+
+		assert_ok!(Poe::create_claim(Origin::signed(1), claim.clone()));
+		assert_ok!(Poe::submit_anchor_receipt(
+			Origin::signed(ANCHOR_KEY),
+			claim.clone(),
+			b"rcpt".to_vec()
+		));
+		assert_noop!(
+			strip_post_info(Poe::submit_anchor_receipt(
+				Origin::signed(ANCHOR_KEY),
+				claim,
+				b"other".to_vec()
+			)),
+			Error::<Test>::ClaimNotPending
+		);
+	});
+}
+
+#[test]
+fn submit_anchor_receipt_failed_when_receipt_too_long() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![1, 2, 3];
+		assert_ok!(Poe::create_claim(Origin::signed(1), claim.clone()));
+
+		assert_noop!(
+			strip_post_info(Poe::submit_anchor_receipt(
+				Origin::signed(ANCHOR_KEY),
+				claim,
+				vec![0; 9]
+			)),
+			Error::<Test>::ReceiptTooLong
+		);
+	});
+}
+
+fn disputed_claim() -> (Vec<u8>, BoundedClaim<Test>) {
+	let claim = vec![1, 2, 3];
+	assert_ok!(Poe::create_claim(Origin::signed(1), claim.clone()));
+	assert_ok!(Poe::dispute_claim(Origin::signed(2), claim.clone(), b"forged".to_vec()));
+	let bounded_claim = BoundedClaim::<Test>::try_from(claim.clone()).unwrap();
+	(claim, bounded_claim)
+}
+
+#[test]
+fn dispute_claim_works() {
+	new_test_ext().execute_with(|| {
+		let (_, bounded_claim) = disputed_claim();
+
+		assert_eq!(Balances::reserved_balance(2), 10);
+		assert_eq!(Poe::claim_status(&bounded_claim), Some(ClaimStatus::Disputed));
+		let dispute = Disputes::<Test>::get(&bounded_claim).unwrap();
+		assert_eq!((dispute.challenger, dispute.bond, dispute.opened), (2, 10, 1));
+		assert_eq!(dispute.reason.into_inner(), b"forged".to_vec());
+		assert_eq!(Event::from(super::Event::ClaimDisputed(2, bounded_claim)), pop_event());
+	});
+}
+
+#[test]
+fn dispute_claim_failed_with_bad_input() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![1, 2, 3];
+		assert_noop!(
+			strip_post_info(Poe::dispute_claim(Origin::signed(2), claim.clone(), vec![])),
+			Error::<Test>::ClaimNotExists
+		);
+
+		assert_ok!(Poe::create_claim(Origin::signed(1), claim.clone()));
+		assert_noop!(
+			strip_post_info(Poe::dispute_claim(Origin::signed(1), claim.clone(), vec![])),
+			Error::<Test>::CannotDisputeOwnClaim
+		);
+		assert_noop!(
+			strip_post_info(Poe::dispute_claim(Origin::signed(2), claim.clone(), vec![0; 9])),
+			Error::<Test>::ReasonTooLong
+		);
+		// Account 3 cannot afford the bond.
+		assert_noop!(
+			strip_post_info(Poe::dispute_claim(Origin::signed(3), claim.clone(), vec![])),
+			pallet_balances::Error::<Test>::InsufficientBalance
+		);
+
+		assert_ok!(Poe::dispute_claim(Origin::signed(2), claim.clone(), vec![]));
+		assert_noop!(
+			strip_post_info(Poe::dispute_claim(Origin::signed(3), claim, vec![])),
+			Error::<Test>::AlreadyDisputed
+		);
+	});
+}
+
+#[test]
+fn disputed_claim_cannot_move() {
+	new_test_ext().execute_with(|| {
+		let (claim, _) = disputed_claim();
+
+		assert_noop!(
+			strip_post_info(Poe::transfer_claim(Origin::signed(1), 3, claim.clone())),
+			Error::<Test>::ClaimDisputed
+		);
+		assert_noop!(
+			strip_post_info(Poe::offer_claim_transfer(Origin::signed(1), 3, claim.clone())),
+			Error::<Test>::ClaimDisputed
+		);
+		assert_noop!(
+			strip_post_info(Poe::revoke_claim(Origin::signed(1), claim)),
+			Error::<Test>::ClaimDisputed
+		);
+	});
+}
+
+#[test]
+fn disputed_claim_refunds_weight() {
+	new_test_ext().execute_with(|| {
+		let (claim, _) = disputed_claim();
+
+		let err = Poe::revoke_claim(Origin::signed(1), claim.clone()).unwrap_err();
+		assert_eq!(err.error, Error::<Test>::ClaimDisputed.into());
+		let dispute_read = <Test as frame_system::Config>::DbWeight::get().reads(1);
+		assert_eq!(
+			err.post_info.actual_weight,
+			Some(<() as WeightInfo>::reject_claim_call(claim.len() as u32) + dispute_read)
+		);
+	});
+}
+
+#[test]
+fn disputed_claim_offer_cannot_be_accepted() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![1, 2, 3];
+		assert_ok!(Poe::create_claim(Origin::signed(1), claim.clone()));
+		assert_ok!(Poe::offer_claim_transfer(Origin::signed(1), 3, claim.clone()));
+		assert_ok!(Poe::dispute_claim(Origin::signed(2), claim.clone(), vec![]));
+
+		assert_noop!(
+			strip_post_info(Poe::accept_claim_transfer(Origin::signed(3), claim)),
+			Error::<Test>::ClaimDisputed
+		);
+	});
+}
+
+#[test]
+fn resolve_dispute_uphold_returns_bond() {
+	new_test_ext().execute_with(|| {
+		let (claim, bounded_claim) = disputed_claim();
+
+		assert_ok!(Poe::resolve_dispute(Origin::root(), claim.clone(), DisputeResolution::Uphold));
+
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(Balances::free_balance(2), 100);
+		assert_eq!(Poe::claim_status(&bounded_claim), Some(ClaimStatus::Active));
+		assert_eq!(Event::from(super::Event::ClaimUpheld(2, bounded_claim)), pop_event());
+		assert_ok!(Poe::transfer_claim(Origin::signed(1), 3, claim));
+	});
+}
+
+#[test]
+fn resolve_dispute_revoke_removes_claim() {
+	new_test_ext().execute_with(|| {
+		let (claim, bounded_claim) = disputed_claim();
+
+		assert_ok!(Poe::resolve_dispute(Origin::root(), claim, DisputeResolution::Revoke));
+
+		assert_eq!(Balances::free_balance(2), 100);
+		assert_eq!(Poe::claim_status(&bounded_claim), None);
+		assert!(!PendingAnchors::<Test>::contains_key(&bounded_claim));
+		assert_eq!(
+			Event::from(super::Event::ClaimRevokedByAuthority(1, bounded_claim)),
+			pop_event()
+		);
+	});
+}
+
+#[test]
+fn resolve_dispute_slash_burns_bond() {
+	new_test_ext().execute_with(|| {
+		let (claim, bounded_claim) = disputed_claim();
+
+		assert_ok!(Poe::resolve_dispute(Origin::root(), claim, DisputeResolution::Slash));
+
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(Balances::free_balance(2), 90);
+		assert_eq!(Poe::claim_status(&bounded_claim), Some(ClaimStatus::Active));
+		assert_eq!(
+			Event::from(super::Event::DisputeBondSlashed(2, bounded_claim, 10)),
+			pop_event()
+		);
+	});
+}
+
+#[test]
+fn resolve_dispute_requires_adjudicator_and_open_dispute() {
+	new_test_ext().execute_with(|| {
+		let claim = vec![1, 2, 3];
+		assert_ok!(Poe::create_claim(Origin::signed(1), claim.clone()));
+		assert_noop!(
+			strip_post_info(Poe::resolve_dispute(
+				Origin::root(),
+				claim.clone(),
+				DisputeResolution::Revoke
+			)),
+			Error::<Test>::NoOpenDispute
+		);
+
+		assert_ok!(Poe::dispute_claim(Origin::signed(2), claim.clone(), vec![]));
+		assert_noop!(
+			strip_post_info(Poe::resolve_dispute(
+				Origin::signed(1),
+				claim,
+				DisputeResolution::Revoke
+			)),
+			DispatchError::BadOrigin
+		);
+		assert_eq!(Balances::reserved_balance(2), 10);
 	});
 }
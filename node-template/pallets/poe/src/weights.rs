@@ -15,31 +15,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! Autogenerated weights for pallet_poe
+//! Estimated weights for pallet_poe
 //!
-//! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 4.0.0-dev
-//! DATE: 2022-10-08, STEPS: `20`, REPEAT: 10, LOW RANGE: `[]`, HIGH RANGE: `[]`
-//! HOSTNAME: `192.168.0.106`, CPU: `<UNKNOWN>`
-//! EXECUTION: None, WASM-EXECUTION: Compiled, CHAIN: Some("dev"), DB CACHE: 1024
-
-// Executed Command:
-// ./target/release/node-template
-// benchmark
-// pallet
-// --chain
-// dev
-// --pallet
-// pallet_poe
-// --extrinsic
-// *
-// --steps
-// 20
-// --repeat
-// 10
-// --output
-// node-template/pallets/poe/src/weights.rs
-// --template
-// .maintain/frame-weight-template.hbs
+//! NOT BENCHMARK OUTPUT: the base weights and per-byte slopes below are hand estimates, scaled
+//! from the 2022-10-08 benchmark run of `create_claim` and `revoke_claim` by the `// Storage:`
+//! accesses of each function. Replace them with measured values by running
+//! `.maintain/benchmark-weights.sh` on reference hardware.
 
 #![cfg_attr(rustfmt, rustfmt_skip)]
 #![allow(unused_parens)]
@@ -52,64 +33,232 @@ use sp_std::marker::PhantomData;
 pub trait WeightInfo {
 	fn create_claim(d: u32, ) -> Weight;
 	fn revoke_claim(d: u32, ) -> Weight;
-	fn transfer_claim(d: u32, ) -> Weight;
+	fn create_batch_claim() -> Weight;
+	fn offer_claim_transfer(d: u32, ) -> Weight;
+	fn accept_claim_transfer(d: u32, ) -> Weight;
+	fn cancel_claim_transfer(d: u32, ) -> Weight;
+	fn reject_oversized_claim() -> Weight;
+	fn reject_claim_call(d: u32, ) -> Weight;
+	fn submit_anchor_receipt(d: u32, r: u32, ) -> Weight;
+	fn dispute_claim(d: u32, r: u32, ) -> Weight;
+	fn resolve_dispute(d: u32, ) -> Weight;
 }
 
 /// Weights for pallet_poe using the Substrate node and recommended hardware.
 pub struct SubstrateWeight<T>(PhantomData<T>);
 impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 	// Storage: PoeModule Proofs (r:1 w:1)
+	// Storage: PoeModule PendingAnchors (r:0 w:1)
 	/// The range of component `d` is `[0, 16]`.
-	fn create_claim(_d: u32, ) -> Weight {
-		(33_817_000 as Weight)
+	fn create_claim(d: u32, ) -> Weight {
+		(32_457_000 as Weight)
+			.saturating_add((91_000 as Weight).saturating_mul(d as Weight))
 			.saturating_add(T::DbWeight::get().reads(1 as Weight))
-			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
 	}
 	// Storage: PoeModule Proofs (r:1 w:1)
+	// Storage: PoeModule PendingTransfers (r:0 w:1)
+	// Storage: PoeModule ClaimHistory (r:0 w:1)
+	// Storage: PoeModule PendingAnchors (r:1 w:1)
+	// Storage: PoeModule AnchorReceipts (r:0 w:1)
+	// Storage: PoeModule Disputes (r:1 w:0)
 	/// The range of component `d` is `[0, 16]`.
 	fn revoke_claim(d: u32, ) -> Weight {
-		(33_180_000 as Weight)
-			// Standard Error: 33_000
-			.saturating_add((100_000 as Weight).saturating_mul(d as Weight))
+		(40_918_000 as Weight)
+			.saturating_add((102_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	// Storage: PoeModule BatchProofs (r:1 w:1)
+	fn create_batch_claim() -> Weight {
+		(27_405_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(1 as Weight))
 			.saturating_add(T::DbWeight::get().writes(1 as Weight))
 	}
+	// Storage: PoeModule Proofs (r:1 w:0)
+	// Storage: PoeModule PendingTransfers (r:0 w:1)
+	// Storage: PoeModule Disputes (r:1 w:0)
+	/// The range of component `d` is `[0, 16]`.
+	fn offer_claim_transfer(d: u32, ) -> Weight {
+		(28_371_000 as Weight)
+			.saturating_add((79_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
 	// Storage: PoeModule Proofs (r:1 w:1)
+	// Storage: PoeModule PendingTransfers (r:1 w:1)
+	// Storage: PoeModule ClaimHistory (r:1 w:1)
+	// Storage: PoeModule Disputes (r:1 w:0)
 	/// The range of component `d` is `[0, 16]`.
-	fn transfer_claim(d: u32, ) -> Weight {
-		(34_922_000 as Weight)
-			// Standard Error: 28_000
-			.saturating_add((65_000 as Weight).saturating_mul(d as Weight))
-			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+	fn accept_claim_transfer(d: u32, ) -> Weight {
+		(49_026_000 as Weight)
+			.saturating_add((112_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: PoeModule Proofs (r:1 w:0)
+	// Storage: PoeModule PendingTransfers (r:1 w:1)
+	/// The range of component `d` is `[0, 16]`.
+	fn cancel_claim_transfer(d: u32, ) -> Weight {
+		(29_688_000 as Weight)
+			.saturating_add((83_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
 			.saturating_add(T::DbWeight::get().writes(1 as Weight))
 	}
+	fn reject_oversized_claim() -> Weight {
+		(9_870_000 as Weight)
+	}
+	// Storage: PoeModule Proofs (r:1 w:0)
+	/// The range of component `d` is `[0, 16]`.
+	fn reject_claim_call(d: u32, ) -> Weight {
+		(17_934_000 as Weight)
+			.saturating_add((61_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+	}
+	// Storage: PoeModule PendingAnchors (r:1 w:1)
+	// Storage: PoeModule AnchorReceipts (r:0 w:1)
+	/// The range of component `d` is `[0, 16]`.
+	/// The range of component `r` is `[0, 128]`.
+	fn submit_anchor_receipt(d: u32, r: u32, ) -> Weight {
+		(31_206_000 as Weight)
+			.saturating_add((86_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add((4_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: PoeModule Proofs (r:1 w:0)
+	// Storage: PoeModule Disputes (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	/// The range of component `d` is `[0, 16]`.
+	/// The range of component `r` is `[0, 256]`.
+	fn dispute_claim(d: u32, r: u32, ) -> Weight {
+		(41_562_000 as Weight)
+			.saturating_add((93_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add((3_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: PoeModule Disputes (r:1 w:1)
+	// Storage: PoeModule Proofs (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: PoeModule PendingTransfers (r:0 w:1)
+	// Storage: PoeModule ClaimHistory (r:0 w:1)
+	// Storage: PoeModule PendingAnchors (r:1 w:1)
+	// Storage: PoeModule AnchorReceipts (r:0 w:1)
+	/// The range of component `d` is `[0, 16]`.
+	fn resolve_dispute(d: u32, ) -> Weight {
+		(47_380_000 as Weight)
+			.saturating_add((108_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
+	}
 }
 
 // For backwards compatibility and tests
 impl WeightInfo for () {
 	// Storage: PoeModule Proofs (r:1 w:1)
+	// Storage: PoeModule PendingAnchors (r:0 w:1)
 	/// The range of component `d` is `[0, 16]`.
-	fn create_claim(_d: u32, ) -> Weight {
-		(33_817_000 as Weight)
+	fn create_claim(d: u32, ) -> Weight {
+		(32_457_000 as Weight)
+			.saturating_add((91_000 as Weight).saturating_mul(d as Weight))
 			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
-			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
 	}
 	// Storage: PoeModule Proofs (r:1 w:1)
+	// Storage: PoeModule PendingTransfers (r:0 w:1)
+	// Storage: PoeModule ClaimHistory (r:0 w:1)
+	// Storage: PoeModule PendingAnchors (r:1 w:1)
+	// Storage: PoeModule AnchorReceipts (r:0 w:1)
+	// Storage: PoeModule Disputes (r:1 w:0)
 	/// The range of component `d` is `[0, 16]`.
 	fn revoke_claim(d: u32, ) -> Weight {
-		(33_180_000 as Weight)
-			// Standard Error: 33_000
-			.saturating_add((100_000 as Weight).saturating_mul(d as Weight))
+		(40_918_000 as Weight)
+			.saturating_add((102_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	// Storage: PoeModule BatchProofs (r:1 w:1)
+	fn create_batch_claim() -> Weight {
+		(27_405_000 as Weight)
 			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
 	}
+	// Storage: PoeModule Proofs (r:1 w:0)
+	// Storage: PoeModule PendingTransfers (r:0 w:1)
+	// Storage: PoeModule Disputes (r:1 w:0)
+	/// The range of component `d` is `[0, 16]`.
+	fn offer_claim_transfer(d: u32, ) -> Weight {
+		(28_371_000 as Weight)
+			.saturating_add((79_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
 	// Storage: PoeModule Proofs (r:1 w:1)
+	// Storage: PoeModule PendingTransfers (r:1 w:1)
+	// Storage: PoeModule ClaimHistory (r:1 w:1)
+	// Storage: PoeModule Disputes (r:1 w:0)
 	/// The range of component `d` is `[0, 16]`.
-	fn transfer_claim(d: u32, ) -> Weight {
-		(34_922_000 as Weight)
-			// Standard Error: 28_000
-			.saturating_add((65_000 as Weight).saturating_mul(d as Weight))
-			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+	fn accept_claim_transfer(d: u32, ) -> Weight {
+		(49_026_000 as Weight)
+			.saturating_add((112_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	// Storage: PoeModule Proofs (r:1 w:0)
+	// Storage: PoeModule PendingTransfers (r:1 w:1)
+	/// The range of component `d` is `[0, 16]`.
+	fn cancel_claim_transfer(d: u32, ) -> Weight {
+		(29_688_000 as Weight)
+			.saturating_add((83_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
 	}
+	fn reject_oversized_claim() -> Weight {
+		(9_870_000 as Weight)
+	}
+	// Storage: PoeModule Proofs (r:1 w:0)
+	/// The range of component `d` is `[0, 16]`.
+	fn reject_claim_call(d: u32, ) -> Weight {
+		(17_934_000 as Weight)
+			.saturating_add((61_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+	}
+	// Storage: PoeModule PendingAnchors (r:1 w:1)
+	// Storage: PoeModule AnchorReceipts (r:0 w:1)
+	/// The range of component `d` is `[0, 16]`.
+	/// The range of component `r` is `[0, 128]`.
+	fn submit_anchor_receipt(d: u32, r: u32, ) -> Weight {
+		(31_206_000 as Weight)
+			.saturating_add((86_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add((4_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: PoeModule Proofs (r:1 w:0)
+	// Storage: PoeModule Disputes (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	/// The range of component `d` is `[0, 16]`.
+	/// The range of component `r` is `[0, 256]`.
+	fn dispute_claim(d: u32, r: u32, ) -> Weight {
+		(41_562_000 as Weight)
+			.saturating_add((93_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add((3_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: PoeModule Disputes (r:1 w:1)
+	// Storage: PoeModule Proofs (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: PoeModule PendingTransfers (r:0 w:1)
+	// Storage: PoeModule ClaimHistory (r:0 w:1)
+	// Storage: PoeModule PendingAnchors (r:1 w:1)
+	// Storage: PoeModule AnchorReceipts (r:0 w:1)
+	/// The range of component `d` is `[0, 16]`.
+	fn resolve_dispute(d: u32, ) -> Weight {
+		(47_380_000 as Weight)
+			.saturating_add((108_000 as Weight).saturating_mul(d as Weight))
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
+	}
 }
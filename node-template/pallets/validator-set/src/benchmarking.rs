@@ -0,0 +1,41 @@
+//! Benchmarking setup for pallet-validator-set
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as ValidatorSet;
+use frame_benchmarking::{account, benchmarks};
+use frame_support::traits::{EnsureOrigin, Get};
+
+fn validator<T: Config>(index: u32) -> T::ValidatorId {
+	T::ValidatorIdOf::convert(account("validator", index, 0)).expect("validators have an id; qed")
+}
+
+// Fill the validator set up to `count` members.
+fn fill_validators<T: Config>(count: u32) {
+	let validators: Vec<_> = (0..count).map(validator::<T>).collect();
+	Validators::<T>::put(ValidatorsOf::<T>::try_from(validators).unwrap());
+}
+
+benchmarks! {
+	add_validator {
+		fill_validators::<T>(T::MaxValidators::get() - 1);
+		let validator_id = validator::<T>(T::MaxValidators::get());
+		let origin = T::AddRemoveOrigin::successful_origin();
+	}: _<T::Origin>(origin, validator_id.clone())
+	verify {
+		assert!(Validators::<T>::get().contains(&validator_id));
+	}
+
+	remove_validator {
+		fill_validators::<T>(T::MaxValidators::get());
+		// The last validator is the most expensive to find.
+		let validator_id = validator::<T>(T::MaxValidators::get() - 1);
+		let origin = T::AddRemoveOrigin::successful_origin();
+	}: _<T::Origin>(origin, validator_id.clone())
+	verify {
+		assert!(!Validators::<T>::get().contains(&validator_id));
+	}
+
+	impl_benchmark_test_suite!(ValidatorSet, crate::mock::new_test_ext(), crate::mock::Test);
+}
@@ -0,0 +1,267 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! A validator set managed through governance instead of staking.
+//!
+//! The pallet is the `SessionManager` of `pallet_session`: validators added or removed by
+//! [`Config::AddRemoveOrigin`] take part from the next session on. It is also the offence
+//! handler of `pallet_offences`: reported validators, e.g. unresponsive ones from
+//! `pallet_im_online` or GRANDPA equivocators, are dropped at the next session, never going below
+//! [`Config::MinAuthorities`]. Offenders that would have been slashed are disabled right away.
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod weights;
+pub use weights::WeightInfo;
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_runtime::{
+	traits::{Convert, Zero},
+	Perbill,
+};
+use sp_staking::{
+	offence::{DisableStrategy, OffenceDetails, OnOffenceHandler},
+	SessionIndex,
+};
+use sp_std::prelude::*;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::WeightInfo;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+	use sp_std::prelude::*;
+
+	pub type ValidatorsOf<T> =
+		BoundedVec<<T as pallet_session::Config>::ValidatorId, <T as Config>::MaxValidators>;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + pallet_session::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// Who can add and remove validators.
+		type AddRemoveOrigin: EnsureOrigin<Self::Origin>;
+
+		/// The validator set never shrinks below this size.
+		#[pallet::constant]
+		type MinAuthorities: Get<u32>;
+
+		/// The maximum size of the validator set.
+		#[pallet::constant]
+		type MaxValidators: Get<u32>;
+
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// The validators of the next session.
+	#[pallet::storage]
+	#[pallet::getter(fn validators)]
+	pub type Validators<T: Config> = StorageValue<_, ValidatorsOf<T>, ValueQuery>;
+
+	/// Validators reported for an offence, removed at the start of the next session.
+	#[pallet::storage]
+	#[pallet::getter(fn offline_validators)]
+	pub type OfflineValidators<T: Config> = StorageValue<_, ValidatorsOf<T>, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		ValidatorAdded(T::ValidatorId),
+		ValidatorRemoved(T::ValidatorId),
+		ValidatorReported(T::ValidatorId),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		Duplicate,
+		NotAValidator,
+		TooManyValidators,
+		TooFewValidators,
+	}
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		pub initial_validators: Vec<T::ValidatorId>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { initial_validators: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			let validators = ValidatorsOf::<T>::try_from(self.initial_validators.clone())
+				.expect("too many initial validators");
+			Validators::<T>::put(validators);
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Add `validator_id` to the validator set from the next session on.
+		#[pallet::weight(<T as Config>::WeightInfo::add_validator())]
+		pub fn add_validator(origin: OriginFor<T>, validator_id: T::ValidatorId) -> DispatchResult {
+			T::AddRemoveOrigin::ensure_origin(origin)?;
+
+			Validators::<T>::try_mutate(|validators| -> DispatchResult {
+				ensure!(!validators.contains(&validator_id), Error::<T>::Duplicate);
+				validators
+					.try_push(validator_id.clone())
+					.map_err(|_| Error::<T>::TooManyValidators)?;
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::ValidatorAdded(validator_id));
+
+			Ok(())
+		}
+
+		/// Remove `validator_id` from the validator set from the next session on.
+		#[pallet::weight(<T as Config>::WeightInfo::remove_validator())]
+		pub fn remove_validator(
+			origin: OriginFor<T>,
+			validator_id: T::ValidatorId,
+		) -> DispatchResult {
+			T::AddRemoveOrigin::ensure_origin(origin)?;
+
+			Validators::<T>::try_mutate(|validators| -> DispatchResult {
+				let position = validators
+					.iter()
+					.position(|v| v == &validator_id)
+					.ok_or(Error::<T>::NotAValidator)?;
+				ensure!(
+					validators.len() as u32 > T::MinAuthorities::get(),
+					Error::<T>::TooFewValidators
+				);
+				validators.remove(position);
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::ValidatorRemoved(validator_id));
+
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Queue `validator_id` for removal at the start of the next session.
+		pub(crate) fn mark_offline(validator_id: T::ValidatorId) {
+			OfflineValidators::<T>::mutate(|offline| {
+				if offline.contains(&validator_id) {
+					return
+				}
+				if offline.try_push(validator_id.clone()).is_ok() {
+					Self::deposit_event(Event::ValidatorReported(validator_id));
+				}
+			});
+		}
+
+		/// Drop the validators reported as offline, keeping at least `MinAuthorities`.
+		pub(crate) fn remove_offline_validators() {
+			let offline = OfflineValidators::<T>::take();
+			if offline.is_empty() {
+				return
+			}
+
+			Validators::<T>::mutate(|validators| {
+				for validator_id in offline {
+					if validators.len() as u32 <= T::MinAuthorities::get() {
+						log::warn!(
+							target: "runtime::validator-set",
+							"keeping offline validators to stay above the minimum set size",
+						);
+						break
+					}
+					if let Some(position) = validators.iter().position(|v| v == &validator_id) {
+						validators.remove(position);
+						Self::deposit_event(Event::ValidatorRemoved(validator_id));
+					}
+				}
+			});
+		}
+	}
+}
+
+impl<T: Config> pallet_session::SessionManager<T::ValidatorId> for Pallet<T> {
+	fn new_session(_new_index: SessionIndex) -> Option<Vec<T::ValidatorId>> {
+		Self::remove_offline_validators();
+		Some(Validators::<T>::get().into_inner())
+	}
+
+	fn end_session(_end_index: SessionIndex) {}
+
+	fn start_session(_start_index: SessionIndex) {}
+}
+
+impl<T: Config> pallet_session::historical::SessionManager<T::ValidatorId, T::ValidatorId>
+	for Pallet<T>
+{
+	fn new_session(new_index: SessionIndex) -> Option<Vec<(T::ValidatorId, T::ValidatorId)>> {
+		<Self as pallet_session::SessionManager<_>>::new_session(new_index)
+			.map(|validators| validators.into_iter().map(|v| (v.clone(), v)).collect())
+	}
+
+	fn end_session(end_index: SessionIndex) {
+		<Self as pallet_session::SessionManager<_>>::end_session(end_index)
+	}
+
+	fn start_session(start_index: SessionIndex) {
+		<Self as pallet_session::SessionManager<_>>::start_session(start_index)
+	}
+}
+
+/// Validators are identified by their own id, both to `pallet_session` and in historical proofs.
+pub struct ValidatorOf<T>(sp_std::marker::PhantomData<T>);
+
+impl<T: Config> Convert<T::ValidatorId, Option<T::ValidatorId>> for ValidatorOf<T> {
+	fn convert(validator_id: T::ValidatorId) -> Option<T::ValidatorId> {
+		Some(validator_id)
+	}
+}
+
+/// There is no stake to slash: every offender loses its seat at the next session, and those
+/// the offence would slash are also disabled for the rest of the current one.
+impl<T: Config> OnOffenceHandler<T::AccountId, (T::ValidatorId, T::ValidatorId), Weight>
+	for Pallet<T>
+{
+	fn on_offence(
+		offenders: &[OffenceDetails<T::AccountId, (T::ValidatorId, T::ValidatorId)>],
+		slash_fraction: &[Perbill],
+		_session: SessionIndex,
+		disable_strategy: DisableStrategy,
+	) -> Weight {
+		let mut disabled: Weight = 0;
+		for (details, fraction) in offenders.iter().zip(slash_fraction) {
+			let (validator_id, _) = &details.offender;
+			Self::mark_offline(validator_id.clone());
+
+			let disable = match disable_strategy {
+				DisableStrategy::Never => false,
+				DisableStrategy::WhenSlashed => !fraction.is_zero(),
+				DisableStrategy::Always => true,
+			};
+			if disable && pallet_session::Pallet::<T>::disable(validator_id) {
+				disabled += 1;
+			}
+		}
+
+		let offenders = offenders.len() as Weight;
+		T::DbWeight::get().reads_writes(offenders, offenders + disabled)
+	}
+}
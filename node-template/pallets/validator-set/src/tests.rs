@@ -0,0 +1,143 @@
+use crate::{mock::*, Error, Event as ValidatorSetEvent};
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::{testing::UintAuthorityId, DispatchError, Perbill};
+use sp_staking::offence::{DisableStrategy, OffenceDetails, OnOffenceHandler};
+
+// A new validator set takes effect one session after it is queued.
+fn rotate_sessions(count: u32) {
+	for _ in 0..count {
+		Session::rotate_session();
+	}
+}
+
+fn set_keys(validator: u64) {
+	frame_system::Pallet::<Test>::inc_providers(&validator);
+	let keys = UintAuthorityId(validator).into();
+	assert_ok!(Session::set_keys(Origin::signed(validator), keys, vec![]));
+}
+
+#[test]
+fn genesis_validators_are_active() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(ValidatorSet::validators().into_inner(), vec![1, 2, 3]);
+		assert_eq!(Session::validators(), vec![1, 2, 3]);
+	});
+}
+
+#[test]
+fn add_validator_works() {
+	new_test_ext().execute_with(|| {
+		set_keys(4);
+		assert_ok!(ValidatorSet::add_validator(Origin::root(), 4));
+		assert_eq!(ValidatorSet::validators().into_inner(), vec![1, 2, 3, 4]);
+		System::assert_last_event(ValidatorSetEvent::ValidatorAdded(4).into());
+
+		rotate_sessions(1);
+		assert_eq!(Session::validators(), vec![1, 2, 3]);
+		rotate_sessions(1);
+		assert_eq!(Session::validators(), vec![1, 2, 3, 4]);
+	});
+}
+
+#[test]
+fn add_validator_fails() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(ValidatorSet::add_validator(Origin::signed(1), 4), DispatchError::BadOrigin);
+		assert_noop!(ValidatorSet::add_validator(Origin::root(), 3), Error::<Test>::Duplicate);
+
+		assert_ok!(ValidatorSet::add_validator(Origin::root(), 4));
+		assert_noop!(
+			ValidatorSet::add_validator(Origin::root(), 5),
+			Error::<Test>::TooManyValidators
+		);
+	});
+}
+
+#[test]
+fn remove_validator_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(ValidatorSet::remove_validator(Origin::root(), 2));
+		assert_eq!(ValidatorSet::validators().into_inner(), vec![1, 3]);
+		System::assert_last_event(ValidatorSetEvent::ValidatorRemoved(2).into());
+
+		rotate_sessions(2);
+		assert_eq!(Session::validators(), vec![1, 3]);
+	});
+}
+
+#[test]
+fn remove_validator_fails() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			ValidatorSet::remove_validator(Origin::signed(1), 2),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			ValidatorSet::remove_validator(Origin::root(), 4),
+			Error::<Test>::NotAValidator
+		);
+
+		assert_ok!(ValidatorSet::remove_validator(Origin::root(), 3));
+		assert_noop!(
+			ValidatorSet::remove_validator(Origin::root(), 2),
+			Error::<Test>::TooFewValidators
+		);
+	});
+}
+
+#[test]
+fn offline_validators_are_removed_at_next_session() {
+	new_test_ext().execute_with(|| {
+		ValidatorSet::mark_offline(2);
+		System::assert_last_event(ValidatorSetEvent::ValidatorReported(2).into());
+		assert_eq!(ValidatorSet::offline_validators().into_inner(), vec![2]);
+
+		rotate_sessions(1);
+		assert_eq!(ValidatorSet::validators().into_inner(), vec![1, 3]);
+		assert!(ValidatorSet::offline_validators().is_empty());
+		rotate_sessions(1);
+		assert_eq!(Session::validators(), vec![1, 3]);
+	});
+}
+
+#[test]
+fn offline_removal_keeps_min_authorities() {
+	new_test_ext().execute_with(|| {
+		ValidatorSet::mark_offline(1);
+		ValidatorSet::mark_offline(2);
+		ValidatorSet::mark_offline(3);
+
+		rotate_sessions(1);
+		assert_eq!(ValidatorSet::validators().into_inner(), vec![2, 3]);
+		assert!(ValidatorSet::offline_validators().is_empty());
+	});
+}
+
+fn report(validator: u64, fraction: Perbill) {
+	let offenders = [OffenceDetails { offender: (validator, validator), reporters: vec![] }];
+	ValidatorSet::on_offence(&offenders, &[fraction], 0, DisableStrategy::WhenSlashed);
+}
+
+#[test]
+fn slashable_offender_is_disabled_and_removed() {
+	new_test_ext().execute_with(|| {
+		report(2, Perbill::from_percent(50));
+		assert_eq!(Session::disabled_validators(), vec![1]);
+		assert_eq!(ValidatorSet::offline_validators().into_inner(), vec![2]);
+
+		rotate_sessions(1);
+		assert_eq!(ValidatorSet::validators().into_inner(), vec![1, 3]);
+	});
+}
+
+#[test]
+fn unslashed_offender_is_only_removed() {
+	new_test_ext().execute_with(|| {
+		report(2, Perbill::from_percent(0));
+		assert!(Session::disabled_validators().is_empty());
+		assert_eq!(ValidatorSet::offline_validators().into_inner(), vec![2]);
+
+		rotate_sessions(1);
+		assert_eq!(ValidatorSet::validators().into_inner(), vec![1, 3]);
+	});
+}
@@ -0,0 +1,143 @@
+//! Minimal binary Merkle tree used for batch notarization in `pallet-poe`.
+//!
+//! Leaves are 32 byte document digests. Leaves and inner nodes are hashed with blake2-256 under
+//! different domain prefixes, and the two children of an inner node are sorted before hashing, so
+//! a proof is simply the list of sibling hashes from the leaf up to the root. A node without a
+//! sibling is promoted to the next level unchanged and contributes nothing to the proof.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use sp_io::hashing::blake2_256;
+use sp_std::prelude::*;
+
+/// A leaf digest, inner node or root of the tree.
+pub type Hash = [u8; 32];
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// Hash a document digest into a leaf node.
+pub fn hash_leaf(leaf: &Hash) -> Hash {
+	let mut buf = [0u8; 33];
+	buf[0] = LEAF_PREFIX;
+	buf[1..].copy_from_slice(leaf);
+	blake2_256(&buf)
+}
+
+/// Hash two sibling nodes into their parent, independently of their order.
+pub fn hash_node(a: &Hash, b: &Hash) -> Hash {
+	let (left, right) = if a <= b { (a, b) } else { (b, a) };
+	let mut buf = [0u8; 65];
+	buf[0] = NODE_PREFIX;
+	buf[1..33].copy_from_slice(left);
+	buf[33..].copy_from_slice(right);
+	blake2_256(&buf)
+}
+
+fn next_level(level: &[Hash]) -> Vec<Hash> {
+	level
+		.chunks(2)
+		.map(|pair| match pair {
+			[left, right] => hash_node(left, right),
+			[single] => *single,
+			_ => unreachable!("chunks(2) yields one or two items; qed"),
+		})
+		.collect()
+}
+
+/// Compute the root of the tree built over `leaves`, or `None` if there are no leaves.
+pub fn merkle_root(leaves: &[Hash]) -> Option<Hash> {
+	if leaves.is_empty() {
+		return None
+	}
+
+	let mut level: Vec<Hash> = leaves.iter().map(hash_leaf).collect();
+	while level.len() > 1 {
+		level = next_level(&level);
+	}
+
+	level.first().copied()
+}
+
+/// Build the inclusion proof of the leaf at `index`, or `None` if `index` is out of bounds.
+pub fn merkle_proof(leaves: &[Hash], index: usize) -> Option<Vec<Hash>> {
+	if index >= leaves.len() {
+		return None
+	}
+
+	let mut proof = Vec::new();
+	let mut position = index;
+	let mut level: Vec<Hash> = leaves.iter().map(hash_leaf).collect();
+	while level.len() > 1 {
+		if let Some(sibling) = level.get(position ^ 1) {
+			proof.push(*sibling);
+		}
+		level = next_level(&level);
+		position /= 2;
+	}
+
+	Some(proof)
+}
+
+/// Check that `leaf` is part of the tree with the given `root`.
+pub fn verify_proof(root: &Hash, leaf: &Hash, proof: &[Hash]) -> bool {
+	let computed = proof.iter().fold(hash_leaf(leaf), |acc, sibling| hash_node(&acc, sibling));
+	&computed == root
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn leaves(n: u8) -> Vec<Hash> {
+		(0..n).map(|i| blake2_256(&[i])).collect()
+	}
+
+	#[test]
+	fn empty_tree_has_no_root() {
+		assert_eq!(merkle_root(&[]), None);
+		assert_eq!(merkle_proof(&[], 0), None);
+	}
+
+	#[test]
+	fn single_leaf_root_is_leaf_hash() {
+		let leaves = leaves(1);
+		let root = merkle_root(&leaves).unwrap();
+		assert_eq!(root, hash_leaf(&leaves[0]));
+		assert_eq!(merkle_proof(&leaves, 0), Some(vec![]));
+		assert!(verify_proof(&root, &leaves[0], &[]));
+	}
+
+	#[test]
+	fn every_leaf_is_provable() {
+		for n in 1..=17 {
+			let leaves = leaves(n);
+			let root = merkle_root(&leaves).unwrap();
+			for (i, leaf) in leaves.iter().enumerate() {
+				let proof = merkle_proof(&leaves, i).unwrap();
+				assert!(verify_proof(&root, leaf, &proof), "leaf {} of {}", i, n);
+			}
+		}
+	}
+
+	#[test]
+	fn proof_does_not_verify_other_leaf_or_root() {
+		let leaves = leaves(5);
+		let root = merkle_root(&leaves).unwrap();
+		let proof = merkle_proof(&leaves, 2).unwrap();
+
+		assert!(!verify_proof(&root, &leaves[3], &proof));
+		assert!(!verify_proof(&merkle_root(&leaves[..4]).unwrap(), &leaves[2], &proof));
+		assert!(!verify_proof(&root, &blake2_256(b"unknown"), &proof));
+	}
+
+	#[test]
+	fn inner_node_is_not_a_valid_leaf() {
+		let leaves = leaves(4);
+		let root = merkle_root(&leaves).unwrap();
+		let inner = hash_node(&hash_leaf(&leaves[0]), &hash_leaf(&leaves[1]));
+		let proof = vec![hash_node(&hash_leaf(&leaves[2]), &hash_leaf(&leaves[3]))];
+
+		assert!(!verify_proof(&root, &inner, &proof));
+	}
+}
@@ -0,0 +1,80 @@
+//! Chain extension giving ink! contracts access to kitties and proof-of-existence claims.
+//!
+//! Every function is identified by the `func_id` the contract passes to
+//! `seal_call_chain_extension`; arguments and results are SCALE encoded. Calls that change state
+//! are dispatched with the contract as the signed origin, so a contract can own and trade kitties
+//! and notarize documents like any account, and the runtime call filter still applies.
+
+use crate::{
+	impls::{dispatch_as, kitty_transfer_weight},
+	AccountId, Call, Runtime, SubstrateKitties,
+};
+use codec::Encode;
+use frame_support::{dispatch::GetDispatchInfo, traits::Get};
+use pallet_contracts::chain_extension::{
+	ChainExtension, Environment, Ext, InitState, RetVal, SysConfig, UncheckedFrom,
+};
+use sp_runtime::DispatchError;
+use sp_std::prelude::*;
+
+/// `(kitty_id: [u8; 16]) -> Option<AccountId>`: the owner of a kitty.
+pub const OWNER_OF: u16 = 0x0001;
+/// `(owner: AccountId) -> Vec<[u8; 16]>`: the kitties owned by an account.
+pub const KITTIES_OF: u16 = 0x0002;
+/// `(kitty_id: [u8; 16]) -> Option<Kitty>`: all details of a kitty.
+pub const KITTY: u16 = 0x0003;
+/// `(to: AccountId, kitty_id: [u8; 16])`: transfer a kitty owned by the contract.
+pub const TRANSFER: u16 = 0x0101;
+/// `(claim: Vec<u8>)`: create a proof-of-existence claim owned by the contract.
+pub const CREATE_CLAIM: u16 = 0x0201;
+
+#[derive(Default)]
+pub struct KittiesExtension;
+
+impl ChainExtension<Runtime> for KittiesExtension {
+	fn call<E>(&mut self, env: Environment<E, InitState>) -> Result<RetVal, DispatchError>
+	where
+		E: Ext<T = Runtime>,
+		<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+	{
+		let func_id = env.func_id();
+		let mut env = env.buf_in_buf_out();
+		let read = <Runtime as frame_system::Config>::DbWeight::get().reads(1);
+
+		match func_id {
+			OWNER_OF => {
+				let kitty_id: [u8; 16] = env.read_as()?;
+				env.charge_weight(read)?;
+				let owner = SubstrateKitties::kitty(kitty_id).map(|kitty| kitty.owner);
+				env.write(&owner.encode(), false, None)?;
+			},
+			KITTIES_OF => {
+				let owner: AccountId = env.read_as()?;
+				env.charge_weight(read)?;
+				let owned = SubstrateKitties::kitties_owned(owner).into_inner();
+				env.write(&owned.encode(), false, None)?;
+			},
+			KITTY => {
+				let kitty_id: [u8; 16] = env.read_as()?;
+				env.charge_weight(read)?;
+				env.write(&SubstrateKitties::kitty(kitty_id).encode(), false, None)?;
+			},
+			TRANSFER => {
+				let (to, kitty_id): (AccountId, [u8; 16]) = env.read_as()?;
+				let call = Call::SubstrateKitties(pallet_kitties::Call::transfer { to, kitty_id });
+				env.charge_weight(kitty_transfer_weight())?;
+				dispatch_as(env.ext().address().clone(), call)?;
+			},
+			CREATE_CLAIM => {
+				let len = env.in_len();
+				let claim: Vec<u8> = env.read_as_unbounded(len)?;
+				let call = Call::PoeModule(pallet_poe::Call::create_claim { claim });
+				env.charge_weight(call.get_dispatch_info().weight)?;
+				dispatch_as(env.ext().address().clone(), call)?;
+			},
+			_ => return Err(DispatchError::Other("unknown chain extension function")),
+		}
+
+		Ok(RetVal::Converging(0))
+	}
+}
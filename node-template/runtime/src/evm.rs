@@ -0,0 +1,262 @@
+//! Ethereum compatibility, compiled with the `evm` feature.
+//!
+//! `pallet_evm` executes contracts and `pallet_ethereum` accepts Ethereum transactions as
+//! self-contained extrinsics, so Solidity tooling can talk to the chain through the node's
+//! `eth_*` RPCs. Kitties and PoE claims are reachable from contracts through the precompiles in
+//! [`crate::precompiles`].
+//!
+//! An Ethereum address maps to the account whose first 20 bytes are the address and the rest
+//! zeroes, and an account maps back to the address made of its first 20 bytes. Accounts created
+//! through the EVM round-trip; a native account shows up under its truncated address, but funds or
+//! kitties sent to that address land in the padded account.
+
+use crate::{
+	precompiles::Precompiles, AccountId, Aura, Balances, BlockWeights, Call, DealWithFees, Event,
+	Origin, Runtime, UncheckedExtrinsic, Weight, WeightToFee, NORMAL_DISPATCH_RATIO,
+	WEIGHT_PER_SECOND,
+};
+use codec::{Decode, Encode};
+use frame_support::{
+	dispatch::Dispatchable, parameter_types, traits::FindAuthor, weights::WeightToFee as _,
+	ConsensusEngineId,
+};
+use pallet_evm::{AddressMapping, EnsureAddressTruncated, FeeCalculator, GasWeightMapping, Runner};
+use sp_core::{H160, H256, U256};
+use sp_runtime::{
+	traits::{DispatchInfoOf, PostDispatchInfoOf, UniqueSaturatedInto},
+	transaction_validity::{TransactionValidity, TransactionValidityError},
+	DispatchResultWithInfo,
+};
+use sp_std::prelude::*;
+
+/// Gas an EVM executes per second of block weight, measured by Frontier on compiled wasm.
+pub const GAS_PER_SECOND: u64 = 40_000_000;
+/// The weight of one unit of gas.
+pub const WEIGHT_PER_GAS: Weight = WEIGHT_PER_SECOND / GAS_PER_SECOND;
+
+/// The Ethereum address of `account`, the inverse of [`PaddedAddressMapping`].
+pub fn evm_address(account: &AccountId) -> H160 {
+	H160::from_slice(&AsRef::<[u8; 32]>::as_ref(account)[..20])
+}
+
+/// Pads an Ethereum address with zeroes into an account.
+pub struct PaddedAddressMapping;
+impl AddressMapping<AccountId> for PaddedAddressMapping {
+	fn into_account_id(address: H160) -> AccountId {
+		let mut account = [0u8; 32];
+		account[..20].copy_from_slice(address.as_bytes());
+		account.into()
+	}
+}
+
+/// Gas is weighed linearly at [`WEIGHT_PER_GAS`].
+pub struct FixedGasWeightMapping;
+impl GasWeightMapping for FixedGasWeightMapping {
+	fn gas_to_weight(gas: u64) -> Weight {
+		gas.saturating_mul(WEIGHT_PER_GAS)
+	}
+
+	fn weight_to_gas(weight: Weight) -> u64 {
+		weight / WEIGHT_PER_GAS
+	}
+}
+
+/// Gas costs what its weight would cost in a native transaction.
+pub struct GasPrice;
+impl FeeCalculator for GasPrice {
+	fn min_gas_price() -> (U256, Weight) {
+		(WeightToFee::weight_to_fee(&WEIGHT_PER_GAS).into(), 0)
+	}
+}
+
+/// The block author as an Ethereum address, see [`evm_address`].
+pub struct FindAuthorAddress;
+impl FindAuthor<H160> for FindAuthorAddress {
+	fn find_author<'a, I>(digests: I) -> Option<H160>
+	where
+		I: 'a + IntoIterator<Item = (ConsensusEngineId, &'a [u8])>,
+	{
+		pallet_session::FindAccountFromAuthorIndex::<Runtime, Aura>::find_author(digests)
+			.map(|author| evm_address(&author))
+	}
+}
+
+parameter_types! {
+	pub const ChainId: u64 = 4242;
+	/// EVM transactions can fill the normal dispatch share of a block.
+	pub BlockGasLimit: U256 = U256::from(
+		FixedGasWeightMapping::weight_to_gas(NORMAL_DISPATCH_RATIO * BlockWeights::get().max_block)
+	);
+	pub PrecompilesValue: Precompiles = Precompiles;
+}
+
+/// Fees of EVM transactions are split like native fees by [`DealWithFees`].
+impl pallet_evm::Config for Runtime {
+	type FeeCalculator = GasPrice;
+	type GasWeightMapping = FixedGasWeightMapping;
+	type BlockHashMapping = pallet_ethereum::EthereumBlockHashMapping<Self>;
+	type CallOrigin = EnsureAddressTruncated;
+	type WithdrawOrigin = EnsureAddressTruncated;
+	type AddressMapping = PaddedAddressMapping;
+	type Currency = Balances;
+	type Event = Event;
+	type PrecompilesType = Precompiles;
+	type PrecompilesValue = PrecompilesValue;
+	type ChainId = ChainId;
+	type BlockGasLimit = BlockGasLimit;
+	type Runner = pallet_evm::runner::stack::Runner<Self>;
+	type OnChargeTransaction = pallet_evm::EVMCurrencyAdapter<Balances, DealWithFees>;
+	type FindAuthor = FindAuthorAddress;
+}
+
+impl pallet_ethereum::Config for Runtime {
+	type Event = Event;
+	type StateRoot = pallet_ethereum::IntermediateStateRoot<Self>;
+}
+
+/// Ethereum transactions carry their own signature instead of a `SignedExtra`.
+impl fp_self_contained::SelfContainedCall for Call {
+	type SignedInfo = H160;
+
+	fn is_self_contained(&self) -> bool {
+		match self {
+			Call::Ethereum(call) => call.is_self_contained(),
+			_ => false,
+		}
+	}
+
+	fn check_self_contained(&self) -> Option<Result<Self::SignedInfo, TransactionValidityError>> {
+		match self {
+			Call::Ethereum(call) => call.check_self_contained(),
+			_ => None,
+		}
+	}
+
+	fn validate_self_contained(
+		&self,
+		info: &Self::SignedInfo,
+		dispatch_info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> Option<TransactionValidity> {
+		match self {
+			Call::Ethereum(call) => call.validate_self_contained(info, dispatch_info, len),
+			_ => None,
+		}
+	}
+
+	fn pre_dispatch_self_contained(
+		&self,
+		info: &Self::SignedInfo,
+		dispatch_info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> Option<Result<(), TransactionValidityError>> {
+		match self {
+			Call::Ethereum(call) => call.pre_dispatch_self_contained(info, dispatch_info, len),
+			_ => None,
+		}
+	}
+
+	fn apply_self_contained(
+		self,
+		info: Self::SignedInfo,
+	) -> Option<DispatchResultWithInfo<PostDispatchInfoOf<Self>>> {
+		match self {
+			call @ Call::Ethereum(pallet_ethereum::Call::transact { .. }) => Some(
+				call.dispatch(Origin::from(pallet_ethereum::RawOrigin::EthereumTransaction(info))),
+			),
+			_ => None,
+		}
+	}
+}
+
+/// Wraps Ethereum transactions received by the `eth_sendRawTransaction` RPC into extrinsics.
+#[derive(Clone)]
+pub struct TransactionConverter;
+
+impl fp_rpc::ConvertTransaction<UncheckedExtrinsic> for TransactionConverter {
+	fn convert_transaction(&self, transaction: pallet_ethereum::Transaction) -> UncheckedExtrinsic {
+		UncheckedExtrinsic::new_unsigned(
+			pallet_ethereum::Call::<Runtime>::transact { transaction }.into(),
+		)
+	}
+}
+
+impl fp_rpc::ConvertTransaction<crate::opaque::UncheckedExtrinsic> for TransactionConverter {
+	fn convert_transaction(
+		&self,
+		transaction: pallet_ethereum::Transaction,
+	) -> crate::opaque::UncheckedExtrinsic {
+		let extrinsic = UncheckedExtrinsic::new_unsigned(
+			pallet_ethereum::Call::<Runtime>::transact { transaction }.into(),
+		);
+		let encoded = extrinsic.encode();
+		crate::opaque::UncheckedExtrinsic::decode(&mut &encoded[..])
+			.expect("Encoded extrinsic is always valid")
+	}
+}
+
+/// Executes an `eth_call` or `eth_estimateGas` against the current state.
+#[allow(clippy::too_many_arguments)]
+pub fn call(
+	from: H160,
+	to: H160,
+	data: Vec<u8>,
+	value: U256,
+	gas_limit: U256,
+	max_fee_per_gas: Option<U256>,
+	max_priority_fee_per_gas: Option<U256>,
+	nonce: Option<U256>,
+	estimate: bool,
+	access_list: Option<Vec<(H160, Vec<H256>)>>,
+) -> Result<pallet_evm::CallInfo, sp_runtime::DispatchError> {
+	let config = evm_config(estimate);
+	<Runtime as pallet_evm::Config>::Runner::call(
+		from,
+		to,
+		data,
+		value,
+		gas_limit.unique_saturated_into(),
+		max_fee_per_gas,
+		max_priority_fee_per_gas,
+		nonce,
+		access_list.unwrap_or_default(),
+		false,
+		&config,
+	)
+	.map_err(|err| err.error.into())
+}
+
+/// Executes a contract creation for `eth_call` or `eth_estimateGas` against the current state.
+#[allow(clippy::too_many_arguments)]
+pub fn create(
+	from: H160,
+	data: Vec<u8>,
+	value: U256,
+	gas_limit: U256,
+	max_fee_per_gas: Option<U256>,
+	max_priority_fee_per_gas: Option<U256>,
+	nonce: Option<U256>,
+	estimate: bool,
+	access_list: Option<Vec<(H160, Vec<H256>)>>,
+) -> Result<pallet_evm::CreateInfo, sp_runtime::DispatchError> {
+	let config = evm_config(estimate);
+	<Runtime as pallet_evm::Config>::Runner::create(
+		from,
+		data,
+		value,
+		gas_limit.unique_saturated_into(),
+		max_fee_per_gas,
+		max_priority_fee_per_gas,
+		nonce,
+		access_list.unwrap_or_default(),
+		false,
+		&config,
+	)
+	.map_err(|err| err.error.into())
+}
+
+fn evm_config(estimate: bool) -> pallet_evm::EvmConfig {
+	let mut config = <Runtime as pallet_evm::Config>::config().clone();
+	config.estimate = estimate;
+	config
+}
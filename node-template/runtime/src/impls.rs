@@ -0,0 +1,57 @@
+//! Runtime glue that doesn't belong to a single pallet.
+
+use crate::{AccountId, Authorship, Balances, Call, Origin, Runtime, Treasury};
+use frame_support::{
+	dispatch::Dispatchable,
+	traits::{Currency, Get, Imbalance, OnUnbalanced},
+	weights::{constants::WEIGHT_PER_MICROS, Weight},
+};
+use sp_runtime::DispatchError;
+
+type NegativeImbalance = <Balances as Currency<AccountId>>::NegativeImbalance;
+
+/// Pays the author of the current block. Burns the funds if there is none.
+pub struct Author;
+impl OnUnbalanced<NegativeImbalance> for Author {
+	fn on_nonzero_unbalanced(amount: NegativeImbalance) {
+		if let Some(author) = Authorship::author() {
+			Balances::resolve_creating(&author, amount);
+		}
+	}
+}
+
+/// Splits transaction fees 80/20 between the treasury and the block author. Tips go to the
+/// author in full.
+///
+/// Fees paid without tips, like those of EVM transactions, are split the same way.
+pub struct DealWithFees;
+impl OnUnbalanced<NegativeImbalance> for DealWithFees {
+	fn on_unbalanceds<B>(mut fees_then_tips: impl Iterator<Item = NegativeImbalance>) {
+		if let Some(fees) = fees_then_tips.next() {
+			Self::on_unbalanced(fees);
+			if let Some(tips) = fees_then_tips.next() {
+				Author::on_unbalanced(tips);
+			}
+		}
+	}
+
+	fn on_nonzero_unbalanced(fees: NegativeImbalance) {
+		let (to_treasury, to_author) = fees.ration(80, 20);
+		Treasury::on_unbalanced(to_treasury);
+		Author::on_unbalanced(to_author);
+	}
+}
+
+/// The weight of `pallet_kitties::Call::transfer`, which the pallet declares as zero, charged to
+/// contracts transferring kitties: the kitty and the kitty lists of both owners are read and
+/// written.
+pub fn kitty_transfer_weight() -> Weight {
+	(40 * WEIGHT_PER_MICROS)
+		.saturating_add(<Runtime as frame_system::Config>::DbWeight::get().reads_writes(3, 3))
+}
+
+/// Dispatch `call` with `origin` as signed origin, on behalf of a contract or a precompile. Its
+/// weight is charged by the caller.
+pub(crate) fn dispatch_as(origin: AccountId, call: Call) -> Result<(), DispatchError> {
+	call.dispatch(Origin::signed(origin)).map(|_| ()).map_err(|e| e.error)
+}
@@ -0,0 +1,45 @@
+//! Storage migrations run by [`crate::Executive`] on runtime upgrades.
+
+use crate::{Council, Runtime, Sudo};
+use frame_support::{
+	storage::{storage_prefix, unhashed},
+	traits::{Get, OnRuntimeUpgrade, PalletInfoAccess, StorageVersion},
+	weights::Weight,
+};
+
+/// The storage version of `Sudo` once [`RemoveSudoKey`] has removed its key.
+const SUDO_KEY_REMOVED: StorageVersion = StorageVersion::new(1);
+
+/// Hands a chain started with a sudo key over to governance: removes `Sudo::Key` once, after
+/// which only `Council` and `Democracy` dispatch privileged calls.
+///
+/// A chain that gets the council with the same upgrade starts with no council members, and
+/// nothing but sudo could elect them: the key is kept while `Council::members()` is empty and
+/// removed on the first runtime upgrade after the council is set. Removing it sets the storage
+/// version of `Sudo` to 1, so a key set again later by governance stays.
+pub struct RemoveSudoKey;
+
+impl OnRuntimeUpgrade for RemoveSudoKey {
+	fn on_runtime_upgrade() -> Weight {
+		let db_weight = <Runtime as frame_system::Config>::DbWeight::get();
+		if StorageVersion::get::<Sudo>() >= SUDO_KEY_REMOVED {
+			return db_weight.reads(1)
+		}
+		if Council::members().is_empty() {
+			return db_weight.reads(2)
+		}
+
+		unhashed::kill(&storage_prefix(Sudo::name().as_bytes(), b"Key"));
+		SUDO_KEY_REMOVED.put::<Sudo>();
+		db_weight.reads_writes(2, 2)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade() -> Result<(), &'static str> {
+		frame_support::ensure!(
+			Council::members().is_empty() || Sudo::key().is_none(),
+			"sudo key still set next to a council"
+		);
+		Ok(())
+	}
+}
@@ -0,0 +1,298 @@
+//! EVM precompiles: the standard Ethereum ones at `0x01` to `0x05`, kitties as an ERC-721 token
+//! at [`KITTIES_ADDRESS`] and PoE notarization at [`POE_ADDRESS`].
+//!
+//! Arguments and results are Solidity ABI encoded. A token id is a kitty's DNA in the low 16 bytes
+//! of the `uint256`. Calls that change state are dispatched with the EVM caller as signed origin,
+//! like the contracts chain extension does, so the runtime call filter still applies. They can't
+//! be made from a static call or through `DELEGATECALL`.
+
+use crate::{
+	evm::{evm_address, FixedGasWeightMapping, PaddedAddressMapping},
+	impls::{dispatch_as, kitty_transfer_weight},
+	Call, PoeModule, Runtime, SubstrateKitties, Weight,
+};
+use frame_support::{dispatch::GetDispatchInfo, traits::Get};
+use hex_literal::hex;
+use pallet_evm::{
+	AddressMapping, ExitRevert, ExitSucceed, GasWeightMapping, Precompile, PrecompileFailure,
+	PrecompileHandle, PrecompileOutput, PrecompileResult, PrecompileSet,
+};
+use pallet_evm_precompile_modexp::Modexp;
+use pallet_evm_precompile_simple::{ECRecover, Identity, Ripemd160, Sha256};
+use sp_core::{H160, H256, U256};
+use sp_std::prelude::*;
+
+/// The address of the kitties ERC-721 precompile.
+pub const KITTIES_ADDRESS: u64 = 0x0400;
+/// The address of the PoE notarization precompile.
+pub const POE_ADDRESS: u64 = 0x0401;
+
+/// `ownerOf(uint256) returns (address)`
+pub const OWNER_OF: [u8; 4] = hex!("6352211e");
+/// `balanceOf(address) returns (uint256)`
+pub const BALANCE_OF: [u8; 4] = hex!("70a08231");
+/// `transferFrom(address from, address to, uint256 tokenId)`, only callable by `from`.
+pub const TRANSFER_FROM: [u8; 4] = hex!("23b872dd");
+/// `createClaim(bytes)`
+pub const CREATE_CLAIM: [u8; 4] = hex!("501eb428");
+/// `claimOwner(bytes) returns (address)`, the zero address for unclaimed documents.
+pub const CLAIM_OWNER: [u8; 4] = hex!("6f6a4b6e");
+
+/// `Transfer(address indexed from, address indexed to, uint256 indexed tokenId)`
+pub const TRANSFER_EVENT: H256 =
+	H256(hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"));
+/// `ClaimCreated(address indexed owner, bytes claim)`
+pub const CLAIM_CREATED_EVENT: H256 =
+	H256(hex!("1e7e4b7bf0919428963fbafef95325226def952f36ff43928a3b72b6f0587868"));
+
+/// The selector of Solidity's `Error(string)`, used to encode revert reasons.
+const ERROR_SELECTOR: [u8; 4] = hex!("08c379a0");
+
+/// Gas of a `LOG0` and of every topic added to it.
+const LOG_GAS: u64 = 375;
+/// Gas of every byte of log data.
+const LOG_DATA_GAS: u64 = 8;
+
+fn hash(address: u64) -> H160 {
+	H160::from_low_u64_be(address)
+}
+
+/// The precompiles of the runtime.
+#[derive(Default)]
+pub struct Precompiles;
+
+impl Precompiles {
+	/// The addresses with a precompile.
+	pub fn used_addresses() -> [H160; 7] {
+		[1, 2, 3, 4, 5, KITTIES_ADDRESS, POE_ADDRESS].map(hash)
+	}
+}
+
+impl PrecompileSet for Precompiles {
+	fn execute(&self, handle: &mut impl PrecompileHandle) -> Option<PrecompileResult> {
+		match handle.code_address() {
+			a if a == hash(1) => Some(ECRecover::execute(handle)),
+			a if a == hash(2) => Some(Sha256::execute(handle)),
+			a if a == hash(3) => Some(Ripemd160::execute(handle)),
+			a if a == hash(4) => Some(Identity::execute(handle)),
+			a if a == hash(5) => Some(Modexp::execute(handle)),
+			a if a == hash(KITTIES_ADDRESS) => Some(KittiesErc721::execute(handle)),
+			a if a == hash(POE_ADDRESS) => Some(PoeNotary::execute(handle)),
+			_ => None,
+		}
+	}
+
+	fn is_precompile(&self, address: H160) -> bool {
+		Self::used_addresses().contains(&address)
+	}
+}
+
+/// `pallet_kitties` as a minimal ERC-721 token, without approvals.
+pub struct KittiesErc721;
+
+impl Precompile for KittiesErc721 {
+	fn execute(handle: &mut impl PrecompileHandle) -> PrecompileResult {
+		let input = handle.input().to_vec();
+		let (selector, args) = Arguments::parse(&input)?;
+
+		match selector {
+			OWNER_OF => {
+				handle.record_cost(read_gas())?;
+				let kitty_id = args.kitty_id(0)?;
+				let kitty =
+					SubstrateKitties::kitty(kitty_id).ok_or_else(|| revert("invalid token ID"))?;
+				Ok(succeed(address_word(evm_address(&kitty.owner))))
+			},
+			BALANCE_OF => {
+				handle.record_cost(read_gas())?;
+				let owner = PaddedAddressMapping::into_account_id(args.address(0)?);
+				let balance = SubstrateKitties::kitties_owned(owner).len();
+				Ok(succeed(uint_word(U256::from(balance as u64))))
+			},
+			TRANSFER_FROM => {
+				ensure_mutable(handle)?;
+				let (from, to, kitty_id) = (args.address(0)?, args.address(1)?, args.kitty_id(2)?);
+				if handle.context().caller != from {
+					return Err(revert("caller is not the token owner"))
+				}
+				let to_account = PaddedAddressMapping::into_account_id(to);
+				dispatch(
+					handle,
+					from,
+					Call::SubstrateKitties(pallet_kitties::Call::transfer {
+						to: to_account,
+						kitty_id,
+					}),
+					kitty_transfer_weight(),
+				)?;
+				log(
+					handle,
+					vec![TRANSFER_EVENT, from.into(), to.into(), token_id(kitty_id)],
+					vec![],
+				)?;
+				Ok(succeed(vec![]))
+			},
+			_ => Err(revert("unknown selector")),
+		}
+	}
+}
+
+/// `pallet_poe` claims created and looked up by EVM accounts.
+pub struct PoeNotary;
+
+impl Precompile for PoeNotary {
+	fn execute(handle: &mut impl PrecompileHandle) -> PrecompileResult {
+		let input = handle.input().to_vec();
+		let (selector, args) = Arguments::parse(&input)?;
+
+		match selector {
+			CREATE_CLAIM => {
+				ensure_mutable(handle)?;
+				let claim = args.bytes(0)?;
+				let caller = handle.context().caller;
+				let call = Call::PoeModule(pallet_poe::Call::create_claim { claim: claim.clone() });
+				let weight = call.get_dispatch_info().weight;
+				dispatch(handle, caller, call, weight)?;
+				log(handle, vec![CLAIM_CREATED_EVENT, caller.into()], bytes_word(&claim))?;
+				Ok(succeed(vec![]))
+			},
+			CLAIM_OWNER => {
+				handle.record_cost(read_gas())?;
+				let claim = args.bytes(0)?;
+				let owner = pallet_poe::BoundedClaim::<Runtime>::try_from(claim)
+					.ok()
+					.and_then(|claim| PoeModule::proofs(&claim))
+					.map(|(owner, _)| evm_address(&owner))
+					.unwrap_or_default();
+				Ok(succeed(address_word(owner)))
+			},
+			_ => Err(revert("unknown selector")),
+		}
+	}
+}
+
+/// The ABI encoded arguments following the function selector.
+struct Arguments<'a>(&'a [u8]);
+
+impl<'a> Arguments<'a> {
+	fn parse(input: &'a [u8]) -> Result<([u8; 4], Self), PrecompileFailure> {
+		let selector = input.get(..4).ok_or_else(|| revert("missing selector"))?;
+		let selector = selector.try_into().expect("slice has length 4; qed");
+		Ok((selector, Arguments(&input[4..])))
+	}
+
+	fn word(&self, index: usize) -> Result<&'a [u8], PrecompileFailure> {
+		self.0.get(index * 32..(index + 1) * 32).ok_or_else(|| revert("input too short"))
+	}
+
+	fn usize(&self, index: usize) -> Result<usize, PrecompileFailure> {
+		let value = U256::from_big_endian(self.word(index)?);
+		if value > U256::from(u32::MAX) {
+			return Err(revert("value out of bounds"))
+		}
+		Ok(value.low_u32() as usize)
+	}
+
+	fn address(&self, index: usize) -> Result<H160, PrecompileFailure> {
+		Ok(H160::from_slice(&self.word(index)?[12..]))
+	}
+
+	/// A token id has the DNA of a kitty in its low 16 bytes and zeroes above.
+	fn kitty_id(&self, index: usize) -> Result<[u8; 16], PrecompileFailure> {
+		let word = self.word(index)?;
+		if word[..16].iter().any(|byte| *byte != 0) {
+			return Err(revert("invalid token ID"))
+		}
+		Ok(word[16..].try_into().expect("word has length 32; qed"))
+	}
+
+	/// Dynamic `bytes`: the word at `index` points to the length, followed by the data.
+	fn bytes(&self, index: usize) -> Result<Vec<u8>, PrecompileFailure> {
+		let offset = self.usize(index)?;
+		let data = self.0.get(offset..).ok_or_else(|| revert("input too short"))?;
+		let data = Arguments(data);
+		let len = data.usize(0)?;
+		data.0
+			.get(32..32usize.saturating_add(len))
+			.map(|bytes| bytes.to_vec())
+			.ok_or_else(|| revert("input too short"))
+	}
+}
+
+fn token_id(kitty_id: [u8; 16]) -> H256 {
+	let mut id = H256::zero();
+	id.0[16..].copy_from_slice(&kitty_id);
+	id
+}
+
+fn address_word(address: H160) -> Vec<u8> {
+	H256::from(address).as_bytes().to_vec()
+}
+
+fn uint_word(value: U256) -> Vec<u8> {
+	let mut word = [0u8; 32];
+	value.to_big_endian(&mut word);
+	word.to_vec()
+}
+
+/// ABI encoding of a single `bytes` or `string` value.
+fn bytes_word(bytes: &[u8]) -> Vec<u8> {
+	let mut encoded = uint_word(U256::from(32u64));
+	encoded.extend(uint_word(U256::from(bytes.len() as u64)));
+	encoded.extend(bytes);
+	encoded.resize(encoded.len() + (32 - bytes.len() % 32) % 32, 0);
+	encoded
+}
+
+fn succeed(output: Vec<u8>) -> PrecompileOutput {
+	PrecompileOutput { exit_status: ExitSucceed::Returned, output }
+}
+
+/// Reverts with `message` as `Error(string)`, which Solidity surfaces as the revert reason.
+fn revert(message: &str) -> PrecompileFailure {
+	let mut output = ERROR_SELECTOR.to_vec();
+	output.extend(bytes_word(message.as_bytes()));
+	PrecompileFailure::Revert { exit_status: ExitRevert::Reverted, output }
+}
+
+fn read_gas() -> u64 {
+	FixedGasWeightMapping::weight_to_gas(
+		<Runtime as frame_system::Config>::DbWeight::get().reads(1),
+	)
+}
+
+fn ensure_mutable(handle: &impl PrecompileHandle) -> Result<(), PrecompileFailure> {
+	if handle.is_static() {
+		return Err(revert("can't change state in a static call"))
+	}
+	// Through `DELEGATECALL` the caller would be whoever called the delegating contract.
+	if handle.context().address != handle.code_address() {
+		return Err(revert("can't be called through DELEGATECALL"))
+	}
+	Ok(())
+}
+
+/// Charge `weight` as gas, then dispatch `call` on behalf of `origin`.
+fn dispatch(
+	handle: &mut impl PrecompileHandle,
+	origin: H160,
+	call: Call,
+	weight: Weight,
+) -> Result<(), PrecompileFailure> {
+	handle.record_cost(FixedGasWeightMapping::weight_to_gas(weight))?;
+	dispatch_as(PaddedAddressMapping::into_account_id(origin), call)
+		.map_err(|error| revert(error.into()))?;
+	Ok(())
+}
+
+fn log(
+	handle: &mut impl PrecompileHandle,
+	topics: Vec<H256>,
+	data: Vec<u8>,
+) -> Result<(), PrecompileFailure> {
+	let gas = LOG_GAS * (1 + topics.len() as u64) + LOG_DATA_GAS * data.len() as u64;
+	handle.record_cost(gas)?;
+	let address = handle.code_address();
+	handle.log(address, topics, data)?;
+	Ok(())
+}
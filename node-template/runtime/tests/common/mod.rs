@@ -0,0 +1,108 @@
+//! Chain setup shared by the runtime integration tests.
+
+// Every test binary includes this module but uses only part of it.
+#![allow(dead_code)]
+
+use codec::Encode;
+use node_template_runtime::{
+	opaque::SessionKeys, AccountId, AuraConfig, Balance, BalancesConfig, Call, Executive,
+	GenesisConfig, GrandpaConfig, Index, SessionConfig, SignedExtra, SignedPayload, SudoConfig,
+	System, SystemConfig, UncheckedExtrinsic, ValidatorSetConfig, UNIT,
+};
+use sp_runtime::{generic::Era, BuildStorage, DispatchOutcome};
+use sp_keyring::{Ed25519Keyring, Sr25519Keyring};
+
+/// The genesis validators. Each one signs GRANDPA messages with the ed25519 key of the same name.
+pub const VALIDATORS: [(Sr25519Keyring, Ed25519Keyring); 3] = [
+	(Sr25519Keyring::Alice, Ed25519Keyring::Alice),
+	(Sr25519Keyring::Bob, Ed25519Keyring::Bob),
+	(Sr25519Keyring::Charlie, Ed25519Keyring::Charlie),
+];
+
+/// The free balance every validator starts with.
+pub const ENDOWMENT: Balance = 1_000 * UNIT;
+
+pub fn validator_accounts() -> Vec<AccountId> {
+	VALIDATORS.iter().map(|(account, _)| account.to_account_id()).collect()
+}
+
+fn session_keys(account: Sr25519Keyring, grandpa: Ed25519Keyring) -> SessionKeys {
+	SessionKeys {
+		aura: account.public().into(),
+		grandpa: grandpa.public().into(),
+		im_online: account.public().into(),
+	}
+}
+
+/// The genesis of the tests: the first session is run by [`VALIDATORS`], each endowed with
+/// [`ENDOWMENT`], and there is no sudo key.
+pub fn genesis() -> GenesisConfig {
+	GenesisConfig {
+		system: SystemConfig { code: vec![] },
+		balances: BalancesConfig {
+			balances: validator_accounts().into_iter().map(|a| (a, ENDOWMENT)).collect(),
+		},
+		aura: AuraConfig { authorities: vec![] },
+		grandpa: GrandpaConfig { authorities: vec![] },
+		sudo: SudoConfig { key: None },
+		council: Default::default(),
+		democracy: Default::default(),
+		treasury: Default::default(),
+		transaction_payment: Default::default(),
+		substrate_kitties: Default::default(),
+		poe_module: Default::default(),
+		validator_set: ValidatorSetConfig { initial_validators: validator_accounts() },
+		session: SessionConfig {
+			keys: VALIDATORS
+				.iter()
+				.map(|(account, grandpa)| {
+					let id = account.to_account_id();
+					(id.clone(), id, session_keys(*account, *grandpa))
+				})
+				.collect(),
+		},
+		im_online: Default::default(),
+		#[cfg(feature = "evm")]
+		evm: Default::default(),
+		#[cfg(feature = "evm")]
+		ethereum: Default::default(),
+	}
+}
+
+/// A chain at block 1 built from `genesis`.
+pub fn new_test_ext_with(genesis: GenesisConfig) -> sp_io::TestExternalities {
+	let mut ext = sp_io::TestExternalities::new(genesis.build_storage().unwrap());
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// A chain at block 1 built from [`genesis`].
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	new_test_ext_with(genesis())
+}
+
+/// Sign `call` as `signer` with the runtime's `SignedExtra`: immortal, at `nonce` and without a
+/// tip.
+pub fn sign(signer: Sr25519Keyring, nonce: Index, call: impl Into<Call>) -> UncheckedExtrinsic {
+	let extra: SignedExtra = (
+		frame_system::CheckNonZeroSender::new(),
+		frame_system::CheckSpecVersion::new(),
+		frame_system::CheckTxVersion::new(),
+		frame_system::CheckGenesis::new(),
+		frame_system::CheckEra::from(Era::Immortal),
+		frame_system::CheckNonce::from(nonce),
+		frame_system::CheckWeight::new(),
+		pallet_transaction_payment::ChargeTransactionPayment::from(0),
+	);
+	let payload = SignedPayload::new(call.into(), extra).unwrap();
+	let signature = payload.using_encoded(|payload| signer.sign(payload));
+	let (call, extra, _) = payload.deconstruct();
+	UncheckedExtrinsic::new_signed(call, signer.to_account_id().into(), signature.into(), extra)
+}
+
+/// Apply `call` signed by `signer` with its next nonce, like a block author would. Panics if the
+/// transaction is invalid, returns the outcome of the dispatch otherwise.
+pub fn submit(signer: Sr25519Keyring, call: impl Into<Call>) -> DispatchOutcome {
+	let nonce = System::account_nonce(signer.to_account_id());
+	Executive::apply_extrinsic(sign(signer, nonce, call)).expect("transaction is valid")
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use codec::{Decode, Encode};
+use common::{new_test_ext, validator_accounts};
+use node_template_runtime::{
+	chain_extension::{CREATE_CLAIM, KITTIES_OF, OWNER_OF, TRANSFER},
+	AccountId, BlockWeights, Contracts, PoeModule, Runtime, SubstrateKitties, Weight, UNIT,
+};
+use pallet_contracts_primitives::{Code, ExecReturnValue};
+use pallet_kitties::Gender;
+use sp_runtime::DispatchError;
+
+const DNA: [u8; 16] = [7; 16];
+
+/// A contract forwarding its input to the chain extension, see `fixtures/chain_extension.wat`.
+fn fixture() -> Vec<u8> {
+	wat::parse_file(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/chain_extension.wat"))
+		.expect("fixture is valid wat; qed")
+}
+
+fn gas_limit() -> Weight {
+	BlockWeights::get().max_block
+}
+
+fn deploy(origin: &AccountId) -> AccountId {
+	let code_hash =
+		Contracts::bare_upload_code(origin.clone(), fixture(), None).unwrap().code_hash;
+	Contracts::bare_instantiate(
+		origin.clone(),
+		UNIT,
+		gas_limit(),
+		None,
+		Code::Existing(code_hash),
+		vec![],
+		vec![],
+		false,
+	)
+	.result
+	.unwrap()
+	.account_id
+}
+
+fn call_extension(
+	origin: &AccountId,
+	contract: &AccountId,
+	func_id: u16,
+	args: impl Encode,
+) -> Result<ExecReturnValue, DispatchError> {
+	let mut input = u32::from(func_id).to_le_bytes().to_vec();
+	input.extend(args.encode());
+	Contracts::bare_call(origin.clone(), contract.clone(), 0, gas_limit(), None, input, false)
+		.result
+}
+
+fn output<T: Decode>(result: Result<ExecReturnValue, DispatchError>) -> T {
+	let result = result.unwrap();
+	assert!(!result.did_revert());
+	T::decode(&mut &result.data.0[..]).unwrap()
+}
+
+#[test]
+fn contract_code_can_be_uploaded() {
+	new_test_ext().execute_with(|| {
+		let alice = &validator_accounts()[0];
+		let result = Contracts::bare_upload_code(alice.clone(), fixture(), None).unwrap();
+		assert!(pallet_contracts::CodeStorage::<Runtime>::contains_key(result.code_hash));
+	});
+}
+
+#[test]
+fn contract_reads_kitties() {
+	new_test_ext().execute_with(|| {
+		let alice = &validator_accounts()[0];
+		let contract = deploy(alice);
+		let owner_of =
+			|| output::<Option<AccountId>>(call_extension(alice, &contract, OWNER_OF, DNA));
+		assert_eq!(owner_of(), None);
+
+		SubstrateKitties::mint(&contract, DNA, Gender::Male).unwrap();
+
+		assert_eq!(owner_of(), Some(contract.clone()));
+		assert_eq!(
+			output::<Vec<[u8; 16]>>(call_extension(alice, &contract, KITTIES_OF, &contract)),
+			vec![DNA]
+		);
+	});
+}
+
+#[test]
+fn contract_transfers_its_kitties() {
+	new_test_ext().execute_with(|| {
+		let accounts = validator_accounts();
+		let (alice, bob) = (&accounts[0], &accounts[1]);
+		let contract = deploy(alice);
+		SubstrateKitties::mint(&contract, DNA, Gender::Female).unwrap();
+
+		output::<()>(call_extension(alice, &contract, TRANSFER, (bob, DNA)));
+		assert_eq!(SubstrateKitties::kitty(DNA).map(|kitty| kitty.owner), Some(bob.clone()));
+
+		// The kitty no longer belongs to the contract.
+		assert_eq!(
+			call_extension(alice, &contract, TRANSFER, (alice, DNA)).unwrap_err(),
+			pallet_kitties::Error::<Runtime>::NotOwner.into()
+		);
+	});
+}
+
+#[test]
+fn contract_creates_claims() {
+	new_test_ext().execute_with(|| {
+		let alice = &validator_accounts()[0];
+		let contract = deploy(alice);
+		let claim = b"sale receipt".to_vec();
+
+		output::<()>(call_extension(alice, &contract, CREATE_CLAIM, &claim));
+
+		let bounded_claim = pallet_poe::BoundedClaim::<Runtime>::try_from(claim).unwrap();
+		assert_eq!(PoeModule::proofs(&bounded_claim).map(|(owner, _)| owner), Some(contract));
+	});
+}
+
+#[test]
+fn unknown_function_fails() {
+	new_test_ext().execute_with(|| {
+		let alice = &validator_accounts()[0];
+		let contract = deploy(alice);
+
+		assert!(call_extension(alice, &contract, 0xffff, ()).is_err());
+	});
+}
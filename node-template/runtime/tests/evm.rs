@@ -0,0 +1,158 @@
+//! The kitties and PoE precompiles, called through the EVM runner like `eth_call` does.
+#![cfg(feature = "evm")]
+
+mod common;
+
+use common::new_test_ext;
+use node_template_runtime::{
+	evm::{self, PaddedAddressMapping},
+	precompiles::{
+		BALANCE_OF, CLAIM_OWNER, CREATE_CLAIM, KITTIES_ADDRESS, OWNER_OF, POE_ADDRESS,
+		TRANSFER_EVENT, TRANSFER_FROM,
+	},
+	SubstrateKitties,
+};
+use pallet_evm::{AddressMapping, CallInfo, ExitReason};
+use pallet_kitties::Gender;
+use sp_core::{H160, H256, U256};
+
+const DNA: [u8; 16] = [7; 16];
+
+fn alice() -> H160 {
+	H160::repeat_byte(0xaa)
+}
+
+fn bob() -> H160 {
+	H160::repeat_byte(0xbb)
+}
+
+/// `bytes` left-padded to an ABI word.
+fn word(bytes: &[u8]) -> Vec<u8> {
+	let mut word = vec![0; 32 - bytes.len()];
+	word.extend(bytes);
+	word
+}
+
+fn token_id() -> Vec<u8> {
+	word(&DNA)
+}
+
+fn address(address: H160) -> Vec<u8> {
+	word(address.as_bytes())
+}
+
+/// A dynamic `bytes` argument when it is the only one.
+fn bytes(data: &[u8]) -> Vec<u8> {
+	let mut encoded = word(&[32]);
+	encoded.extend(word(&[data.len() as u8]));
+	encoded.extend(data);
+	encoded.resize(encoded.len() + (32 - data.len() % 32) % 32, 0);
+	encoded
+}
+
+fn call(from: H160, precompile: u64, selector: [u8; 4], args: &[Vec<u8>]) -> CallInfo {
+	let mut input = selector.to_vec();
+	input.extend(args.concat());
+	evm::call(
+		from,
+		H160::from_low_u64_be(precompile),
+		input,
+		U256::zero(),
+		U256::from(1_000_000u64),
+		None,
+		None,
+		None,
+		false,
+		None,
+	)
+	.unwrap()
+}
+
+fn succeeded(info: &CallInfo) -> bool {
+	matches!(info.exit_reason, ExitReason::Succeed(_))
+}
+
+fn mint_to(owner: H160) {
+	SubstrateKitties::mint(&PaddedAddressMapping::into_account_id(owner), DNA, Gender::Male)
+		.unwrap();
+}
+
+fn owner() -> Option<H160> {
+	SubstrateKitties::kitty(DNA).map(|kitty| evm::evm_address(&kitty.owner))
+}
+
+#[test]
+fn kitties_are_read_as_erc721_tokens() {
+	new_test_ext().execute_with(|| {
+		mint_to(alice());
+
+		let info = call(bob(), KITTIES_ADDRESS, OWNER_OF, &[token_id()]);
+		assert!(succeeded(&info));
+		assert_eq!(info.value, address(alice()));
+
+		let info = call(bob(), KITTIES_ADDRESS, BALANCE_OF, &[address(alice())]);
+		assert_eq!(info.value, word(&[1]));
+		let info = call(bob(), KITTIES_ADDRESS, BALANCE_OF, &[address(bob())]);
+		assert_eq!(info.value, word(&[0]));
+	});
+}
+
+#[test]
+fn owner_of_unknown_kitty_reverts() {
+	new_test_ext().execute_with(|| {
+		let info = call(bob(), KITTIES_ADDRESS, OWNER_OF, &[token_id()]);
+		assert!(matches!(info.exit_reason, ExitReason::Revert(_)));
+	});
+}
+
+#[test]
+fn transfer_from_moves_the_kitty_and_logs_it() {
+	new_test_ext().execute_with(|| {
+		mint_to(alice());
+
+		let info = call(
+			alice(),
+			KITTIES_ADDRESS,
+			TRANSFER_FROM,
+			&[address(alice()), address(bob()), token_id()],
+		);
+
+		assert!(succeeded(&info));
+		assert_eq!(owner(), Some(bob()));
+		assert_eq!(
+			info.logs[0].topics,
+			vec![TRANSFER_EVENT, alice().into(), bob().into(), H256::from_slice(&token_id())]
+		);
+	});
+}
+
+#[test]
+fn transfer_from_must_be_called_by_the_owner() {
+	new_test_ext().execute_with(|| {
+		mint_to(alice());
+
+		let info = call(
+			bob(),
+			KITTIES_ADDRESS,
+			TRANSFER_FROM,
+			&[address(alice()), address(bob()), token_id()],
+		);
+
+		assert!(matches!(info.exit_reason, ExitReason::Revert(_)));
+		assert_eq!(owner(), Some(alice()));
+	});
+}
+
+#[test]
+fn claims_are_notarized_and_looked_up() {
+	new_test_ext().execute_with(|| {
+		let claim = bytes(b"sale receipt");
+		let info = call(alice(), POE_ADDRESS, CLAIM_OWNER, &[claim.clone()]);
+		assert_eq!(info.value, address(H160::zero()));
+
+		assert!(succeeded(&call(alice(), POE_ADDRESS, CREATE_CLAIM, &[claim.clone()])));
+
+		let info = call(bob(), POE_ADDRESS, CLAIM_OWNER, &[claim]);
+		assert_eq!(info.value, address(alice()));
+	});
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use codec::Encode;
+use common::{new_test_ext, ENDOWMENT, VALIDATORS};
+use frame_support::{
+	traits::{Currency, Get, OnUnbalanced},
+	weights::{DispatchClass, WeightToFee as _},
+};
+use node_template_runtime::{
+	impls::DealWithFees, Balances, ExtrinsicBaseWeight, MinimumMultiplier, Runtime, System,
+	Treasury, WeightToFee, MILLIUNIT, UNIT,
+};
+use pallet_transaction_payment::Multiplier;
+use sp_consensus_aura::{Slot, AURA_ENGINE_ID};
+use sp_runtime::{
+	traits::Convert,
+	Digest, DigestItem, FixedPointNumber, Perbill,
+};
+
+/// Start block 2 in the given Aura slot, which makes the validator at `slot % 3` its author.
+fn start_block_in_slot(slot: u64) {
+	let pre_digest = DigestItem::PreRuntime(AURA_ENGINE_ID, Slot::from(slot).encode());
+	System::initialize(&2, &Default::default(), &Digest { logs: vec![pre_digest] });
+}
+
+/// The fee multiplier following `previous` after a block filled up to `fullness`.
+fn next_multiplier(fullness: Perbill, previous: Multiplier) -> Multiplier {
+	let max_normal = <Runtime as frame_system::Config>::BlockWeights::get()
+		.get(DispatchClass::Normal)
+		.max_total
+		.unwrap();
+	System::set_block_consumed_resources(fullness * max_normal, 0);
+	<Runtime as pallet_transaction_payment::Config>::FeeMultiplierUpdate::convert(previous)
+}
+
+#[test]
+fn base_extrinsic_costs_a_tenth_of_a_milliunit() {
+	let fee = WeightToFee::weight_to_fee(&ExtrinsicBaseWeight::get());
+	assert!(fee <= MILLIUNIT / 10 && fee >= MILLIUNIT / 10 - 1, "unexpected fee {}", fee);
+}
+
+#[test]
+fn fee_multiplier_follows_block_fullness() {
+	new_test_ext().execute_with(|| {
+		let one = Multiplier::saturating_from_integer(1);
+
+		assert!(next_multiplier(Perbill::from_percent(0), one) < one);
+		assert_eq!(next_multiplier(Perbill::from_percent(25), one), one);
+		let half_full = next_multiplier(Perbill::from_percent(50), one);
+		assert!(half_full > one);
+		assert!(next_multiplier(Perbill::from_percent(100), one) > half_full);
+	});
+}
+
+#[test]
+fn fee_multiplier_has_a_floor() {
+	new_test_ext().execute_with(|| {
+		let minimum = MinimumMultiplier::get();
+		assert_eq!(next_multiplier(Perbill::from_percent(0), minimum), minimum);
+	});
+}
+
+#[test]
+fn fees_are_split_between_treasury_and_author() {
+	new_test_ext().execute_with(|| {
+		start_block_in_slot(1);
+		let author = VALIDATORS[1].0.to_account_id();
+		let issuance = Balances::total_issuance();
+
+		let fee = Balances::issue(10 * UNIT);
+		let tip = Balances::issue(UNIT);
+		DealWithFees::on_unbalanceds(vec![fee, tip].into_iter());
+
+		assert_eq!(Balances::free_balance(Treasury::account_id()), 8 * UNIT);
+		assert_eq!(Balances::free_balance(author), ENDOWMENT + 3 * UNIT);
+		assert_eq!(Balances::total_issuance(), issuance + 11 * UNIT);
+	});
+}
+
+#[test]
+fn author_share_is_burned_without_author() {
+	new_test_ext().execute_with(|| {
+		let issuance = Balances::total_issuance();
+
+		let fee = Balances::issue(10 * UNIT);
+		DealWithFees::on_unbalanceds(vec![fee].into_iter());
+
+		assert_eq!(Balances::free_balance(Treasury::account_id()), 8 * UNIT);
+		assert_eq!(Balances::total_issuance(), issuance + 8 * UNIT);
+	});
+}
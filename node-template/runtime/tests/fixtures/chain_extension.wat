@@ -0,0 +1,36 @@
+;; Forwards its input to the chain extension and returns the extension's output.
+;;
+;; The input is the `func_id` as a little endian u32 followed by the encoded arguments.
+(module
+	(import "seal0" "seal_call_chain_extension"
+		(func $seal_call_chain_extension (param i32 i32 i32 i32 i32) (result i32))
+	)
+	(import "seal0" "seal_input" (func $seal_input (param i32 i32)))
+	(import "seal0" "seal_return" (func $seal_return (param i32 i32 i32)))
+	(import "env" "memory" (memory 1 1))
+
+	;; [0, 4) size of the input buffer
+	(data (i32.const 0) "\00\01")
+
+	;; [4, 8) size of the output buffer
+	(data (i32.const 4) "\00\01")
+
+	;; [16, 272) input buffer
+	;; [512, 768) output buffer
+
+	(func (export "deploy"))
+
+	(func (export "call")
+		(call $seal_input (i32.const 16) (i32.const 0))
+		(drop
+			(call $seal_call_chain_extension
+				(i32.load (i32.const 16))
+				(i32.const 20)
+				(i32.sub (i32.load (i32.const 0)) (i32.const 4))
+				(i32.const 512)
+				(i32.const 4)
+			)
+		)
+		(call $seal_return (i32.const 0) (i32.const 512) (i32.load (i32.const 4)))
+	)
+)
@@ -0,0 +1,100 @@
+mod common;
+
+use common::{new_test_ext, VALIDATORS};
+use frame_support::{
+	assert_noop, assert_ok, dispatch::DispatchResultWithPostInfo, traits::KeyOwnerProofSystem,
+};
+use node_template_runtime::{
+	BlockNumber, Event, Grandpa, Historical, Origin, Runtime, Session, System, ValidatorSet,
+};
+use pallet_grandpa::fg_primitives;
+use sp_core::H256;
+use sp_keyring::Ed25519Keyring;
+
+/// Two prevotes of `offender` for different blocks at the same height in the same round.
+fn double_vote(
+	set_id: fg_primitives::SetId,
+	round: fg_primitives::RoundNumber,
+	offender: Ed25519Keyring,
+	targets: (H256, H256),
+) -> fg_primitives::EquivocationProof<H256, BlockNumber> {
+	let signed_prevote = |target_hash: H256| {
+		let prevote = finality_grandpa::Prevote { target_hash, target_number: 1 };
+		let message = finality_grandpa::Message::Prevote(prevote.clone());
+		let payload = fg_primitives::localized_payload(round, set_id, &message);
+		(prevote, offender.sign(&payload).into())
+	};
+
+	fg_primitives::EquivocationProof::new(
+		set_id,
+		fg_primitives::Equivocation::Prevote(finality_grandpa::Equivocation {
+			round_number: round,
+			identity: offender.public().into(),
+			first: signed_prevote(targets.0),
+			second: signed_prevote(targets.1),
+		}),
+	)
+}
+
+fn report(
+	proof: fg_primitives::EquivocationProof<H256, BlockNumber>,
+	offender: Ed25519Keyring,
+) -> DispatchResultWithPostInfo {
+	let authority_id = fg_primitives::AuthorityId::from(offender.public());
+	let key_owner_proof = Historical::prove((fg_primitives::KEY_TYPE, authority_id)).unwrap();
+	Grandpa::report_equivocation_unsigned(Origin::none(), Box::new(proof), key_owner_proof)
+}
+
+#[test]
+fn equivocating_validator_is_disabled_and_removed() {
+	new_test_ext().execute_with(|| {
+		let (account, offender) = VALIDATORS[0];
+		let offender_id = account.to_account_id();
+		let proof = double_vote(0, 1, offender, (H256::repeat_byte(1), H256::repeat_byte(2)));
+
+		assert_ok!(report(proof, offender));
+
+		assert!(System::events().iter().any(|record| matches!(
+			record.event,
+			Event::Offences(pallet_offences::Event::Offence { .. })
+		)));
+		assert!(ValidatorSet::offline_validators().contains(&offender_id));
+		let index = Session::validators().iter().position(|v| v == &offender_id).unwrap();
+		assert!(Session::disabled_validators().contains(&(index as u32)));
+
+		// The offender is gone from the set queued at the next session...
+		Session::rotate_session();
+		assert!(!ValidatorSet::validators().contains(&offender_id));
+		// ...and no longer validates from the one after.
+		Session::rotate_session();
+		assert!(!Session::validators().contains(&offender_id));
+	});
+}
+
+#[test]
+fn equivocation_is_punished_once() {
+	new_test_ext().execute_with(|| {
+		let (_, offender) = VALIDATORS[1];
+		let proof = double_vote(0, 1, offender, (H256::repeat_byte(1), H256::repeat_byte(2)));
+
+		assert_ok!(report(proof.clone(), offender));
+		assert_noop!(
+			report(proof, offender),
+			pallet_grandpa::Error::<Runtime>::DuplicateOffenceReport
+		);
+	});
+}
+
+#[test]
+fn same_vote_twice_is_not_an_equivocation() {
+	new_test_ext().execute_with(|| {
+		let (account, offender) = VALIDATORS[2];
+		let proof = double_vote(0, 1, offender, (H256::repeat_byte(1), H256::repeat_byte(1)));
+
+		assert_noop!(
+			report(proof, offender),
+			pallet_grandpa::Error::<Runtime>::InvalidEquivocationProof
+		);
+		assert!(!ValidatorSet::offline_validators().contains(&account.to_account_id()));
+	});
+}
@@ -0,0 +1,44 @@
+//! The migrations of the next runtime upgrade, run on chains built from older genesis configs.
+
+mod common;
+
+use common::{genesis, new_test_ext_with};
+use frame_support::traits::{GenesisBuild, OnRuntimeUpgrade, StorageVersion};
+use node_template_runtime::{migrations::RemoveSudoKey, CouncilConfig, Runtime, Sudo, SudoConfig};
+use sp_keyring::Sr25519Keyring::{self, Alice, Bob};
+
+/// A chain with Alice's sudo key and `council`, as started before the council could govern it.
+fn ext_with_sudo_key(council: Vec<Sr25519Keyring>) -> sp_io::TestExternalities {
+	let mut genesis = genesis();
+	genesis.sudo = SudoConfig { key: Some(Alice.to_account_id()) };
+	genesis.council = CouncilConfig {
+		members: council.iter().map(|member| member.to_account_id()).collect(),
+		phantom: Default::default(),
+	};
+	new_test_ext_with(genesis)
+}
+
+#[test]
+fn sudo_key_is_kept_without_a_council() {
+	ext_with_sudo_key(vec![]).execute_with(|| {
+		RemoveSudoKey::on_runtime_upgrade();
+
+		assert_eq!(Sudo::key(), Some(Alice.to_account_id()));
+		assert_eq!(StorageVersion::get::<Sudo>(), 0);
+	});
+}
+
+#[test]
+fn sudo_key_is_removed_once_there_is_a_council() {
+	ext_with_sudo_key(vec![Bob]).execute_with(|| {
+		RemoveSudoKey::on_runtime_upgrade();
+
+		assert_eq!(Sudo::key(), None);
+		assert_eq!(StorageVersion::get::<Sudo>(), 1);
+
+		// A key set again by governance survives the next upgrades.
+		GenesisBuild::<Runtime>::build(&SudoConfig { key: Some(Bob.to_account_id()) });
+		RemoveSudoKey::on_runtime_upgrade();
+		assert_eq!(Sudo::key(), Some(Bob.to_account_id()));
+	});
+}
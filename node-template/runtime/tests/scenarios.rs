@@ -0,0 +1,130 @@
+//! End-to-end scenarios through signed extrinsics, catching mistakes in how the pallets are wired
+//! into the runtime.
+
+mod common;
+
+use codec::Encode;
+use common::{genesis, new_test_ext, new_test_ext_with, sign, submit, validator_accounts, ENDOWMENT};
+use frame_support::{assert_ok, traits::Get, weights::GetDispatchInfo};
+use node_template_runtime::{
+	Balances, Call, Executive, PoeModule, Runtime, SubstrateKitties, SubstrateKittiesConfig,
+	System, TransactionPayment, UNIT,
+};
+use pallet_kitties::Gender;
+use sp_io::hashing::blake2_128;
+use sp_keyring::Sr25519Keyring::{Alice, Bob, Charlie};
+use sp_runtime::transaction_validity::{InvalidTransaction, TransactionValidityError};
+
+const DNA: [u8; 16] = [7; 16];
+
+fn create_kitty() -> Call {
+	Call::SubstrateKitties(pallet_kitties::Call::create_kitty {})
+}
+
+fn create_claim(claim: Vec<u8>) -> Call {
+	Call::PoeModule(pallet_poe::Call::create_claim { claim })
+}
+
+#[test]
+fn genesis_config_builds_the_chain() {
+	let mut genesis = genesis();
+	genesis.substrate_kitties =
+		SubstrateKittiesConfig { kitties: vec![(Charlie.to_account_id(), DNA, Gender::Female)] };
+
+	new_test_ext_with(genesis).execute_with(|| {
+		assert_eq!(pallet_session::Validators::<Runtime>::get(), validator_accounts());
+		assert_eq!(Balances::free_balance(Alice.to_account_id()), ENDOWMENT);
+		assert_eq!(
+			SubstrateKitties::kitty(DNA).map(|kitty| kitty.owner),
+			Some(Charlie.to_account_id())
+		);
+	});
+}
+
+#[test]
+fn signed_extrinsics_pay_their_fee() {
+	new_test_ext().execute_with(|| {
+		let alice = Alice.to_account_id();
+		let xt = sign(Alice, 0, create_kitty());
+		let len = xt.encode().len() as u32;
+		let fee = TransactionPayment::compute_fee(len, &xt.get_dispatch_info(), 0);
+		assert!(fee > 0);
+
+		assert_ok!(Executive::apply_extrinsic(xt).unwrap());
+
+		assert_eq!(Balances::free_balance(&alice), ENDOWMENT - fee);
+		assert_eq!(System::account_nonce(&alice), 1);
+		assert_eq!(SubstrateKitties::kitties_owned(&alice).len(), 1);
+	});
+}
+
+#[test]
+fn replayed_extrinsic_is_rejected() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(submit(Alice, create_kitty()));
+
+		assert_eq!(
+			Executive::apply_extrinsic(sign(Alice, 0, create_kitty())),
+			Err(TransactionValidityError::Invalid(InvalidTransaction::Stale))
+		);
+	});
+}
+
+#[test]
+fn kitty_sale_is_notarized() {
+	new_test_ext().execute_with(|| {
+		let (alice, bob) = (Alice.to_account_id(), Bob.to_account_id());
+		let price = 10 * UNIT;
+
+		assert_ok!(submit(Alice, create_kitty()));
+		let kitty = SubstrateKitties::kitties_owned(&alice)[0];
+		assert_ok!(submit(
+			Alice,
+			pallet_kitties::Call::<Runtime>::set_price { kitty_id: kitty, new_price: Some(price) }
+		));
+
+		let seller_balance = Balances::free_balance(&alice);
+		assert_ok!(submit(
+			Bob,
+			pallet_kitties::Call::<Runtime>::buy_kitty { kitty_id: kitty, limit_price: price }
+		));
+		assert_eq!(SubstrateKitties::kitty(kitty).map(|kitty| kitty.owner), Some(bob.clone()));
+		assert_eq!(Balances::free_balance(&alice), seller_balance + price);
+
+		// A receipt doesn't fit in a claim, its hash does.
+		let receipt = (kitty, &alice, &bob, price, System::block_number()).encode();
+		let claim = blake2_128(&receipt).to_vec();
+		assert_ok!(submit(Bob, create_claim(claim.clone())));
+
+		let claim = pallet_poe::BoundedClaim::<Runtime>::try_from(claim).unwrap();
+		assert_eq!(PoeModule::proofs(&claim).map(|(owner, _)| owner), Some(bob));
+	});
+}
+
+#[test]
+fn claims_are_limited_to_claim_limit_size() {
+	new_test_ext().execute_with(|| {
+		let limit = <Runtime as pallet_poe::Config>::ClaimLimitSize::get() as usize;
+
+		assert_ok!(submit(Alice, create_claim(vec![1; limit])));
+		assert_eq!(
+			submit(Alice, create_claim(vec![2; limit + 1])),
+			Err(pallet_poe::Error::<Runtime>::ClaimTooLong.into())
+		);
+	});
+}
+
+#[test]
+fn kitties_are_limited_to_max_kitties_owned() {
+	new_test_ext().execute_with(|| {
+		let max = <Runtime as pallet_kitties::Config>::MaxKittiesOwned::get();
+
+		for _ in 0..max {
+			assert_ok!(submit(Alice, create_kitty()));
+		}
+		assert_eq!(
+			submit(Alice, create_kitty()),
+			Err(pallet_kitties::Error::<Runtime>::TooManyOwned.into())
+		);
+	});
+}